@@ -0,0 +1,481 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::opcodes::{self, AddressingMode, Opcode};
+
+/// Assembles `source` as if it were loaded at $8000.
+///
+/// Statements are separated by newlines or `;`. Supported syntax covers the
+/// official opcodes in every addressing mode, `label:` definitions, `<label` /
+/// `>label` for the low / high byte, and the `.byte` / `.word` directives.
+/// Numbers are `$hex`, `%binary` or decimal; a literal written with more than
+/// two hex digits (or above 255) always selects the absolute form.
+pub fn assemble(source: &str) -> Result<Vec<u8>, AsmError> {
+    assemble_at(0x8000, source)
+}
+
+pub fn assemble_at(origin: u16, source: &str) -> Result<Vec<u8>, AsmError> {
+    let mut labels = BTreeMap::new();
+    let mut statements = Vec::new();
+    let mut address = origin;
+
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        for statement in text.split(';') {
+            let mut rest = statement.trim();
+            if let Some((label, after)) = split_label(rest) {
+                if labels.insert(label.to_string(), address).is_some() {
+                    return Err(AsmError::new(
+                        line,
+                        AsmErrorKind::DuplicateLabel(label.to_string()),
+                    ));
+                }
+                rest = after.trim();
+            }
+            if rest.is_empty() {
+                continue;
+            }
+            let item = parse_item(rest).map_err(|kind| AsmError::new(line, kind))?;
+            let size = item.size();
+            statements.push(Statement {
+                line,
+                address,
+                item,
+            });
+            address = address.wrapping_add(size as u16);
+        }
+    }
+
+    let mut output = Vec::new();
+    for statement in &statements {
+        statement
+            .emit(&labels, &mut output)
+            .map_err(|kind| AsmError::new(statement.line, kind))?;
+    }
+    Ok(output)
+}
+
+/// Assembles a program at $8000, panicking on invalid source.
+///
+/// `asm!("LDA #$10; STA $0200")` or `asm!(0x0600, "...")` for another origin.
+#[macro_export]
+macro_rules! asm {
+    ($source:expr) => {
+        $crate::asm::assemble($source).expect("invalid 6502 assembly")
+    };
+    ($origin:expr, $source:expr) => {
+        $crate::asm::assemble_at($origin, $source).expect("invalid 6502 assembly")
+    };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsmError {
+    /// 1-based source line.
+    pub line: usize,
+    pub kind: AsmErrorKind,
+}
+
+impl AsmError {
+    fn new(line: usize, kind: AsmErrorKind) -> Self {
+        AsmError { line, kind }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsmErrorKind {
+    UnknownMnemonic(String),
+    InvalidOperand(String),
+    UnsupportedMode(String),
+    UndefinedLabel(String),
+    DuplicateLabel(String),
+    ValueTooLarge(u16),
+    BranchOutOfRange(i32),
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            AsmErrorKind::UnknownMnemonic(name) => write!(f, "unknown mnemonic `{}`", name),
+            AsmErrorKind::InvalidOperand(text) => write!(f, "invalid operand `{}`", text),
+            AsmErrorKind::UnsupportedMode(name) => {
+                write!(f, "addressing mode not supported by {}", name)
+            }
+            AsmErrorKind::UndefinedLabel(name) => write!(f, "undefined label `{}`", name),
+            AsmErrorKind::DuplicateLabel(name) => write!(f, "label `{}` defined twice", name),
+            AsmErrorKind::ValueTooLarge(value) => {
+                write!(f, "${:04X} does not fit in a byte", value)
+            }
+            AsmErrorKind::BranchOutOfRange(offset) => {
+                write!(f, "branch offset {} out of range", offset)
+            }
+        }
+    }
+}
+
+impl core::error::Error for AsmError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    Full,
+    Low,
+    High,
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Literal(u16),
+    Label(String),
+}
+
+#[derive(Debug, Clone)]
+struct Expr {
+    value: Value,
+    part: Part,
+    /// Needs 16 bits, so zero page forms must not be chosen.
+    wide: bool,
+}
+
+impl Expr {
+    fn resolve(&self, labels: &BTreeMap<String, u16>) -> Result<u16, AsmErrorKind> {
+        let value = match &self.value {
+            Value::Literal(value) => *value,
+            Value::Label(name) => *labels
+                .get(name)
+                .ok_or_else(|| AsmErrorKind::UndefinedLabel(name.clone()))?,
+        };
+        Ok(match self.part {
+            Part::Full => value,
+            Part::Low => value & 0xFF,
+            Part::High => value >> 8,
+        })
+    }
+
+    fn resolve_byte(&self, labels: &BTreeMap<String, u16>) -> Result<u8, AsmErrorKind> {
+        let value = self.resolve(labels)?;
+        if value > 0xFF {
+            return Err(AsmErrorKind::ValueTooLarge(value));
+        }
+        Ok(value as u8)
+    }
+}
+
+enum Item {
+    Instruction {
+        opcode: &'static Opcode,
+        operand: Option<Expr>,
+    },
+    Bytes(Vec<Expr>),
+    Words(Vec<Expr>),
+}
+
+impl Item {
+    fn size(&self) -> usize {
+        match self {
+            Item::Instruction { opcode, .. } => opcode.len(),
+            Item::Bytes(values) => values.len(),
+            Item::Words(values) => values.len() * 2,
+        }
+    }
+}
+
+struct Statement {
+    line: usize,
+    address: u16,
+    item: Item,
+}
+
+impl Statement {
+    fn emit(
+        &self,
+        labels: &BTreeMap<String, u16>,
+        output: &mut Vec<u8>,
+    ) -> Result<(), AsmErrorKind> {
+        match &self.item {
+            Item::Instruction { opcode, operand } => {
+                output.push(opcode.code);
+                let operand = match operand {
+                    Some(operand) => operand,
+                    None => return Ok(()),
+                };
+                match opcode.mode {
+                    AddressingMode::Relative => {
+                        let target = operand.resolve(labels)? as i32;
+                        let offset = target - (self.address as i32 + 2);
+                        if !(-128..=127).contains(&offset) {
+                            return Err(AsmErrorKind::BranchOutOfRange(offset));
+                        }
+                        output.push(offset as u8);
+                    }
+                    mode if mode.operand_len() == 1 => output.push(operand.resolve_byte(labels)?),
+                    _ => output.extend_from_slice(&operand.resolve(labels)?.to_le_bytes()),
+                }
+            }
+            Item::Bytes(values) => {
+                for value in values {
+                    output.push(value.resolve_byte(labels)?);
+                }
+            }
+            Item::Words(values) => {
+                for value in values {
+                    output.extend_from_slice(&value.resolve(labels)?.to_le_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn split_label(statement: &str) -> Option<(&str, &str)> {
+    let (label, rest) = statement.split_once(':')?;
+    if is_identifier(label) {
+        Some((label, rest))
+    } else {
+        None
+    }
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_item(statement: &str) -> Result<Item, AsmErrorKind> {
+    let (mnemonic, operand) = match statement.split_once(char::is_whitespace) {
+        Some((mnemonic, operand)) => (mnemonic, operand.trim()),
+        None => (statement, ""),
+    };
+
+    if mnemonic.eq_ignore_ascii_case(".byte") {
+        return Ok(Item::Bytes(parse_list(operand)?));
+    }
+    if mnemonic.eq_ignore_ascii_case(".word") {
+        return Ok(Item::Words(parse_list(operand)?));
+    }
+    if !opcodes::is_mnemonic(mnemonic) {
+        return Err(AsmErrorKind::UnknownMnemonic(mnemonic.to_string()));
+    }
+
+    let (candidates, operand): (&[AddressingMode], Option<Expr>) = parse_operand(operand)?;
+    let wide = operand.as_ref().is_some_and(|expr| expr.wide);
+    let opcode = candidates
+        .iter()
+        .filter(|mode| !(wide && is_zero_page(**mode)))
+        .find_map(|mode| opcodes::lookup(mnemonic, *mode))
+        .ok_or_else(|| AsmErrorKind::UnsupportedMode(mnemonic.to_ascii_uppercase()))?;
+    Ok(Item::Instruction { opcode, operand })
+}
+
+fn is_zero_page(mode: AddressingMode) -> bool {
+    matches!(
+        mode,
+        AddressingMode::ZeroPage | AddressingMode::ZeroPageX | AddressingMode::ZeroPageY
+    )
+}
+
+/// Returns the addressing modes the operand syntax could mean, in order of preference.
+fn parse_operand(text: &str) -> Result<(&'static [AddressingMode], Option<Expr>), AsmErrorKind> {
+    use AddressingMode::*;
+
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let upper = compact.to_ascii_uppercase();
+
+    if compact.is_empty() {
+        return Ok((&[Implied, Accumulator], None));
+    }
+    if upper == "A" {
+        return Ok((&[Accumulator], None));
+    }
+    if let Some(value) = compact.strip_prefix('#') {
+        return Ok((&[Immediate], Some(parse_expr(value)?)));
+    }
+    if let Some(inner) = compact.strip_prefix('(') {
+        if upper.ends_with(",X)") {
+            return Ok((&[IndirectX], Some(parse_expr(&inner[..inner.len() - 3])?)));
+        }
+        if upper.ends_with("),Y") {
+            return Ok((&[IndirectY], Some(parse_expr(&inner[..inner.len() - 3])?)));
+        }
+        if let Some(inner) = inner.strip_suffix(')') {
+            return Ok((&[Indirect], Some(parse_expr(inner)?)));
+        }
+        return Err(AsmErrorKind::InvalidOperand(text.to_string()));
+    }
+    if upper.ends_with(",X") {
+        let expr = parse_expr(&compact[..compact.len() - 2])?;
+        return Ok((&[ZeroPageX, AbsoluteX], Some(expr)));
+    }
+    if upper.ends_with(",Y") {
+        let expr = parse_expr(&compact[..compact.len() - 2])?;
+        return Ok((&[ZeroPageY, AbsoluteY], Some(expr)));
+    }
+    Ok((&[Relative, ZeroPage, Absolute], Some(parse_expr(&compact)?)))
+}
+
+fn parse_list(text: &str) -> Result<Vec<Expr>, AsmErrorKind> {
+    text.split(',')
+        .map(|value| parse_expr(value.trim()))
+        .collect()
+}
+
+fn parse_expr(text: &str) -> Result<Expr, AsmErrorKind> {
+    let invalid = || AsmErrorKind::InvalidOperand(text.to_string());
+
+    let (part, body) = if let Some(body) = text.strip_prefix('<') {
+        (Part::Low, body)
+    } else if let Some(body) = text.strip_prefix('>') {
+        (Part::High, body)
+    } else {
+        (Part::Full, text)
+    };
+
+    let (value, wide) = if let Some(digits) = body.strip_prefix('$') {
+        let value = u16::from_str_radix(digits, 16).map_err(|_| invalid())?;
+        (Value::Literal(value), digits.len() > 2)
+    } else if let Some(digits) = body.strip_prefix('%') {
+        let value = u16::from_str_radix(digits, 2).map_err(|_| invalid())?;
+        (Value::Literal(value), digits.len() > 8)
+    } else if body.starts_with(|c: char| c.is_ascii_digit()) {
+        let value: u16 = body.parse().map_err(|_| invalid())?;
+        (Value::Literal(value), value > 0xFF)
+    } else if is_identifier(body) {
+        (Value::Label(body.to_string()), true)
+    } else {
+        return Err(invalid());
+    };
+
+    Ok(Expr {
+        value,
+        wide: wide && part == Part::Full,
+        part,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn error(source: &str) -> AsmError {
+        assemble(source).unwrap_err()
+    }
+
+    #[test]
+    fn every_addressing_mode() {
+        let bytes = assemble(
+            "CLC
+             ASL A
+             LSR
+             LDA #$10
+             LDA $10
+             LDA $10,X
+             LDX $10,Y
+             LDA $1234
+             LDA $1234,X
+             LDA $1234,Y
+             JMP ($1234)
+             LDA ($10,X)
+             LDA ($10),Y
+             BNE $8000",
+        )
+        .unwrap();
+        assert_eq!(
+            bytes,
+            vec![
+                0x18, 0x0A, 0x4A, 0xA9, 0x10, 0xA5, 0x10, 0xB5, 0x10, 0xB6, 0x10, 0xAD, 0x34, 0x12,
+                0xBD, 0x34, 0x12, 0xB9, 0x34, 0x12, 0x6C, 0x34, 0x12, 0xA1, 0x10, 0xB1, 0x10, 0xD0,
+                0xE3,
+            ]
+        );
+    }
+
+    #[test]
+    fn wide_literals_and_labels_select_absolute() {
+        assert_eq!(assemble("LDA $0010").unwrap(), vec![0xAD, 0x10, 0x00]);
+        assert_eq!(assemble("LDA 300").unwrap(), vec![0xAD, 0x2C, 0x01]);
+        assert_eq!(assemble("LDA 16").unwrap(), vec![0xA5, 0x10]);
+        // LDA has no zero page,Y form, so a short operand falls back to absolute
+        assert_eq!(assemble("LDA $10,Y").unwrap(), vec![0xB9, 0x10, 0x00]);
+    }
+
+    #[test]
+    fn branches_forward_and_backward() {
+        let bytes = assemble("back: DEX; BNE back; BEQ ahead; NOP; ahead: RTS").unwrap();
+        assert_eq!(bytes, vec![0xCA, 0xD0, 0xFD, 0xF0, 0x01, 0xEA, 0x60]);
+    }
+
+    #[test]
+    fn branch_out_of_range() {
+        let mut source = String::from("start: NOP\n");
+        for _ in 0..127 {
+            source.push_str("NOP\n");
+        }
+        source.push_str("BNE start");
+        assert_eq!(
+            error(&source),
+            AsmError::new(129, AsmErrorKind::BranchOutOfRange(-130))
+        );
+
+        let far = assemble_at(0x8000, ".byte 0\nBEQ $8100").unwrap_err();
+        assert_eq!(far.kind, AsmErrorKind::BranchOutOfRange(253));
+    }
+
+    #[test]
+    fn byte_selectors_and_data() {
+        let bytes = assemble_at(
+            0xC000,
+            "LDA #<data; LDX #>data; RTS
+             data: .byte 1, $02, %11
+             .word data, $1234",
+        )
+        .unwrap();
+        assert_eq!(
+            bytes,
+            vec![0xA9, 0x05, 0xA2, 0xC0, 0x60, 0x01, 0x02, 0x03, 0x05, 0xC0, 0x34, 0x12]
+        );
+    }
+
+    #[test]
+    fn label_errors() {
+        assert_eq!(
+            error("a: NOP\na: NOP"),
+            AsmError::new(2, AsmErrorKind::DuplicateLabel("a".into()))
+        );
+        assert_eq!(
+            error("NOP\nJMP nowhere"),
+            AsmError::new(2, AsmErrorKind::UndefinedLabel("nowhere".into()))
+        );
+    }
+
+    #[test]
+    fn operand_errors() {
+        assert_eq!(
+            error("FOO #1").kind,
+            AsmErrorKind::UnknownMnemonic("FOO".into())
+        );
+        assert_eq!(
+            error("STA #1").kind,
+            AsmErrorKind::UnsupportedMode("STA".into())
+        );
+        assert_eq!(error("LDA #$100").kind, AsmErrorKind::ValueTooLarge(0x100));
+        assert_eq!(
+            error("LDA #$1G").kind,
+            AsmErrorKind::InvalidOperand("$1G".into())
+        );
+    }
+
+    #[test]
+    fn macro_assembles_at_origin() {
+        assert_eq!(crate::asm!("NOP"), vec![0xEA]);
+        assert_eq!(
+            crate::asm!(0x0600, "here: JMP here"),
+            vec![0x4C, 0x00, 0x06]
+        );
+    }
+}
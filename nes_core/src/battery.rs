@@ -0,0 +1,178 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Battery-backed PRG RAM kept in sync with a save file.
+///
+/// Writes only mark the RAM dirty; `tick` writes it out once the flush
+/// interval has passed since the last flush, and dropping the RAM flushes
+/// whatever is left, so a killed process loses at most one interval of
+/// progress.
+#[derive(Debug)]
+pub struct BatteryRam {
+    data: Vec<u8>,
+    path: PathBuf,
+    dirty: bool,
+    flush_interval: Duration,
+    last_flush: Instant,
+}
+
+impl BatteryRam {
+    pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+    /// Loads `path` if it exists (padded or truncated to `size`), otherwise
+    /// starts from zeroed RAM.
+    pub fn open(path: impl Into<PathBuf>, size: usize) -> io::Result<Self> {
+        let path = path.into();
+        let mut data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error),
+        };
+        data.resize(size, 0);
+        Ok(BatteryRam {
+            data,
+            path,
+            dirty: false,
+            flush_interval: Self::DEFAULT_FLUSH_INTERVAL,
+            last_flush: Instant::now(),
+        })
+    }
+
+    pub fn set_flush_interval(&mut self, interval: Duration) {
+        self.flush_interval = interval;
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn read(&self, offset: usize) -> u8 {
+        self.data[offset]
+    }
+
+    pub fn write(&mut self, offset: usize, value: u8) {
+        if self.data[offset] != value {
+            self.data[offset] = value;
+            self.dirty = true;
+        }
+    }
+
+    /// Call regularly, e.g. once per frame. Returns whether a flush happened.
+    pub fn tick(&mut self) -> io::Result<bool> {
+        if !self.dirty || self.last_flush.elapsed() < self.flush_interval {
+            return Ok(false);
+        }
+        self.flush()?;
+        Ok(true)
+    }
+
+    /// Writes the RAM out now if it changed, creating the save directory if
+    /// needed. The data goes to a temporary file that is synced before it
+    /// replaces the save, so a crash mid-write cannot corrupt the existing one.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let mut file = File::create(&temporary)?;
+        file.write_all(&self.data)?;
+        file.sync_all()?;
+        fs::rename(&temporary, &self.path)?;
+        self.dirty = false;
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+}
+
+impl Drop for BatteryRam {
+    fn drop(&mut self) {
+        // nowhere to report the error; the previous save file is left intact
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system temp dir, unique to `name`.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nes_core-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn writing_the_same_value_stays_clean() {
+        let dir = temp_dir("same-value");
+        let mut ram = BatteryRam::open(dir.join("game.sav"), 16).unwrap();
+        ram.write(3, 0);
+        assert!(!ram.is_dirty());
+        ram.write(3, 7);
+        assert!(ram.is_dirty());
+        assert_eq!(ram.read(3), 7);
+        drop(ram);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn tick_waits_for_the_interval() {
+        let dir = temp_dir("tick");
+        let path = dir.join("game.sav");
+        let mut ram = BatteryRam::open(&path, 4).unwrap();
+        ram.set_flush_interval(Duration::from_secs(3600));
+        ram.write(0, 1);
+        assert!(!ram.tick().unwrap());
+        assert!(!path.exists());
+
+        ram.set_flush_interval(Duration::ZERO);
+        assert!(ram.tick().unwrap());
+        assert!(!ram.is_dirty());
+        assert_eq!(fs::read(&path).unwrap(), [1, 0, 0, 0]);
+        // nothing new to write
+        assert!(!ram.tick().unwrap());
+        drop(ram);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn drop_flushes_and_open_reloads() {
+        let dir = temp_dir("drop");
+        let path = dir.join("game.sav");
+        let mut ram = BatteryRam::open(&path, 4).unwrap();
+        ram.write(2, 0xAB);
+        drop(ram);
+        assert_eq!(fs::read(&path).unwrap(), [0, 0, 0xAB, 0]);
+
+        let ram = BatteryRam::open(&path, 6).unwrap();
+        assert_eq!(ram.data(), [0, 0, 0xAB, 0, 0, 0]);
+        drop(ram);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn flush_creates_missing_directories() {
+        let dir = temp_dir("missing-dir");
+        let path = dir.join("saves").join("0123ABCD").join("battery.sav");
+        let mut ram = BatteryRam::open(&path, 2).unwrap();
+        ram.write(1, 5);
+        ram.flush().unwrap();
+        assert_eq!(fs::read(&path).unwrap(), [0, 5]);
+        drop(ram);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,318 @@
+use alloc::vec::Vec;
+
+/// Comparison applied between the previous snapshot and the current RAM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchFilter {
+    Unchanged,
+    Changed,
+    Increased,
+    Decreased,
+    /// Exactly `n` more than before, wrapping at 256.
+    IncreasedBy(u8),
+    DecreasedBy(u8),
+    EqualTo(u8),
+}
+
+impl SearchFilter {
+    fn matches(self, previous: u8, current: u8) -> bool {
+        match self {
+            SearchFilter::Unchanged => current == previous,
+            SearchFilter::Changed => current != previous,
+            SearchFilter::Increased => current > previous,
+            SearchFilter::Decreased => current < previous,
+            SearchFilter::IncreasedBy(n) => current == previous.wrapping_add(n),
+            SearchFilter::DecreasedBy(n) => current == previous.wrapping_sub(n),
+            SearchFilter::EqualTo(value) => current == value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    pub address: u16,
+    pub previous: u8,
+    pub current: u8,
+}
+
+/// Iteratively narrows down RAM addresses, e.g. to find where lives are stored.
+///
+/// Addresses are offsets into the RAM slice passed in, so for work RAM they
+/// are CPU addresses.
+#[derive(Debug, Clone)]
+pub struct CheatSearch {
+    previous: Vec<u8>,
+    current: Vec<u8>,
+    candidates: Vec<u16>,
+}
+
+impl CheatSearch {
+    /// Starts a search with every address of `ram` as a candidate.
+    pub fn new(ram: &[u8]) -> Self {
+        CheatSearch {
+            previous: ram.to_vec(),
+            current: ram.to_vec(),
+            candidates: (0..ram.len().min(0x10000))
+                .map(|address| address as u16)
+                .collect(),
+        }
+    }
+
+    pub fn reset(&mut self, ram: &[u8]) {
+        *self = CheatSearch::new(ram);
+    }
+
+    /// Keeps the candidates whose change since the last filter matches, then
+    /// takes `ram` as the new snapshot.
+    pub fn filter(&mut self, ram: &[u8], filter: SearchFilter) {
+        self.previous = core::mem::replace(&mut self.current, ram.to_vec());
+        let (previous, current) = (&self.previous, &self.current);
+        self.candidates.retain(|address| {
+            let address = *address as usize;
+            match (previous.get(address), current.get(address)) {
+                (Some(previous), Some(current)) => filter.matches(*previous, *current),
+                _ => false,
+            }
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    pub fn candidates(&self) -> impl Iterator<Item = Candidate> + '_ {
+        self.candidates.iter().map(|address| Candidate {
+            address: *address,
+            previous: self.previous[*address as usize],
+            current: self.current[*address as usize],
+        })
+    }
+
+    /// Drops an address the user has ruled out by hand.
+    pub fn exclude(&mut self, address: u16) {
+        self.candidates.retain(|candidate| *candidate != address);
+    }
+}
+
+/// A raw `address:value` cheat that keeps `address` frozen at `value`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cheat {
+    pub address: u16,
+    pub value: u8,
+    pub enabled: bool,
+}
+
+impl Cheat {
+    pub fn new(address: u16, value: u8) -> Self {
+        Cheat {
+            address,
+            value,
+            enabled: true,
+        }
+    }
+
+    /// Parses the `AAAA:VV` hex form, e.g. `0075:09`.
+    pub fn parse(text: &str) -> Option<Cheat> {
+        let (address, value) = text.trim().split_once(':')?;
+        // from_str_radix alone would also take a sign or any number of digits
+        let hex = |field: &str, digits| {
+            field.len() == digits && field.bytes().all(|c| c.is_ascii_hexdigit())
+        };
+        if !hex(address, 4) || !hex(value, 2) {
+            return None;
+        }
+        Some(Cheat::new(
+            u16::from_str_radix(address, 16).ok()?,
+            u8::from_str_radix(value, 16).ok()?,
+        ))
+    }
+}
+
+/// Active freeze cheats.
+///
+/// The core can either call `apply` after every instruction, or route bus
+/// writes through `filter_write` so a frozen address never changes at all.
+#[derive(Debug, Clone, Default)]
+pub struct CheatList {
+    cheats: Vec<Cheat>,
+}
+
+impl CheatList {
+    pub fn new() -> Self {
+        CheatList::default()
+    }
+
+    /// Adds a cheat, replacing any existing one for the same address.
+    pub fn add(&mut self, cheat: Cheat) {
+        self.remove(cheat.address);
+        self.cheats.push(cheat);
+    }
+
+    pub fn remove(&mut self, address: u16) {
+        self.cheats.retain(|cheat| cheat.address != address);
+    }
+
+    pub fn set_enabled(&mut self, address: u16, enabled: bool) {
+        for cheat in self
+            .cheats
+            .iter_mut()
+            .filter(|cheat| cheat.address == address)
+        {
+            cheat.enabled = enabled;
+        }
+    }
+
+    pub fn cheats(&self) -> &[Cheat] {
+        &self.cheats
+    }
+
+    /// Forces every enabled cheat into `memory`, indexed by CPU address.
+    pub fn apply(&self, memory: &mut [u8]) {
+        for cheat in self.cheats.iter().filter(|cheat| cheat.enabled) {
+            if let Some(byte) = memory.get_mut(cheat.address as usize) {
+                *byte = cheat.value;
+            }
+        }
+    }
+
+    /// The value that should actually be stored by a write of `value` to `address`.
+    pub fn filter_write(&self, address: u16, value: u8) -> u8 {
+        self.cheats
+            .iter()
+            .find(|cheat| cheat.enabled && cheat.address == address)
+            .map_or(value, |cheat| cheat.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addresses(search: &CheatSearch) -> Vec<u16> {
+        search
+            .candidates()
+            .map(|candidate| candidate.address)
+            .collect()
+    }
+
+    /// Filters a one-step change from `before` to `after`.
+    fn survivors(before: &[u8], after: &[u8], filter: SearchFilter) -> Vec<u16> {
+        let mut search = CheatSearch::new(before);
+        search.filter(after, filter);
+        addresses(&search)
+    }
+
+    #[test]
+    fn each_filter() {
+        let before = [5, 5, 5, 5, 0xFF, 0x00];
+        let after = [5, 6, 4, 9, 0x01, 0xFE];
+        assert_eq!(survivors(&before, &after, SearchFilter::Unchanged), [0]);
+        assert_eq!(
+            survivors(&before, &after, SearchFilter::Changed),
+            [1, 2, 3, 4, 5]
+        );
+        assert_eq!(
+            survivors(&before, &after, SearchFilter::Increased),
+            [1, 3, 5]
+        );
+        assert_eq!(survivors(&before, &after, SearchFilter::Decreased), [2, 4]);
+        assert_eq!(survivors(&before, &after, SearchFilter::EqualTo(5)), [0]);
+    }
+
+    #[test]
+    fn increased_and_decreased_by_wrap() {
+        let before = [5, 0xFF, 0x00, 5];
+        let after = [7, 0x01, 0xFE, 3];
+        assert_eq!(
+            survivors(&before, &after, SearchFilter::IncreasedBy(2)),
+            [0, 1]
+        );
+        assert_eq!(
+            survivors(&before, &after, SearchFilter::DecreasedBy(2)),
+            [2, 3]
+        );
+    }
+
+    #[test]
+    fn filters_compare_against_the_last_snapshot() {
+        let mut search = CheatSearch::new(&[3, 3, 3]);
+        search.filter(&[2, 3, 2], SearchFilter::Decreased);
+        assert_eq!(addresses(&search), [0, 2]);
+
+        // compared with the first snapshot, address 2 would match instead
+        search.filter(&[1, 3, 2], SearchFilter::DecreasedBy(1));
+        assert_eq!(
+            search.candidates().collect::<Vec<_>>(),
+            [Candidate {
+                address: 0,
+                previous: 2,
+                current: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn exclude_and_reset() {
+        let mut search = CheatSearch::new(&[0; 4]);
+        search.exclude(2);
+        assert_eq!(addresses(&search), [0, 1, 3]);
+        search.reset(&[0; 2]);
+        assert_eq!(search.len(), 2);
+    }
+
+    #[test]
+    fn ram_length_changes_drop_missing_addresses() {
+        let mut search = CheatSearch::new(&[1, 1, 1, 1]);
+        search.filter(&[1, 1], SearchFilter::Unchanged);
+        assert_eq!(addresses(&search), [0, 1]);
+        search.filter(&[1; 8], SearchFilter::Unchanged);
+        assert_eq!(addresses(&search), [0, 1]);
+        search.filter(&[], SearchFilter::Unchanged);
+        assert!(search.is_empty());
+    }
+
+    #[test]
+    fn parses_only_the_aaaa_vv_form() {
+        assert_eq!(Cheat::parse(" 0075:09 "), Some(Cheat::new(0x75, 9)));
+        assert_eq!(Cheat::parse("07fF:a0"), Some(Cheat::new(0x7FF, 0xA0)));
+        for bad in [
+            "+75:9", "0075:+9", "75:09", "00075:09", "0075:9", "0075:009", "0075", "00G5:09",
+        ] {
+            assert_eq!(Cheat::parse(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn add_replaces_the_cheat_at_the_same_address() {
+        let mut list = CheatList::new();
+        list.add(Cheat::new(0x10, 1));
+        list.add(Cheat::new(0x20, 2));
+        list.add(Cheat::new(0x10, 3));
+        assert_eq!(list.cheats(), [Cheat::new(0x20, 2), Cheat::new(0x10, 3)]);
+        list.remove(0x20);
+        assert_eq!(list.cheats(), [Cheat::new(0x10, 3)]);
+    }
+
+    #[test]
+    fn apply_and_filter_write_skip_disabled_cheats() {
+        let mut list = CheatList::new();
+        list.add(Cheat::new(0x01, 0x63));
+        list.add(Cheat::new(0x02, 0x09));
+        // out of range for the slice below, ignored
+        list.add(Cheat::new(0x800, 0xFF));
+        list.set_enabled(0x02, false);
+
+        let mut memory = [0; 4];
+        list.apply(&mut memory);
+        assert_eq!(memory, [0, 0x63, 0, 0]);
+        assert_eq!(list.filter_write(0x01, 0x05), 0x63);
+        assert_eq!(list.filter_write(0x02, 0x05), 0x05);
+        assert_eq!(list.filter_write(0x03, 0x05), 0x05);
+
+        list.set_enabled(0x02, true);
+        assert_eq!(list.filter_write(0x02, 0x05), 0x09);
+    }
+}
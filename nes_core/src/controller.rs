@@ -0,0 +1,147 @@
+use alloc::boxed::Box;
+use core::any::Any;
+
+/// A peripheral plugged into a controller port (or the Famicom expansion port).
+///
+/// The `Any` bound lets the host get the concrete device back out of
+/// `ControllerPorts` to feed it input.
+pub trait ControllerDevice: Any {
+    /// $4016 write. Every device sees the whole value; bit 0 is the strobe.
+    fn write(&mut self, data: u8);
+
+    /// Read of the port register, advancing any shift register. Only the
+    /// bits the device drives may be set.
+    fn read(&mut self) -> u8;
+
+    /// What `read` would return, without side effects.
+    fn peek(&self) -> u8;
+}
+
+/// The two controller ports plus the Famicom expansion port.
+///
+/// Expansion devices such as the Family BASIC keyboard answer on $4017, so
+/// their bits are merged with port 2's.
+#[derive(Default)]
+pub struct ControllerPorts {
+    ports: [Option<Box<dyn ControllerDevice>>; 2],
+    expansion: Option<Box<dyn ControllerDevice>>,
+}
+
+impl ControllerPorts {
+    pub fn new() -> Self {
+        ControllerPorts::default()
+    }
+
+    /// Plugs `device` into port 0 ($4016) or 1 ($4017), returning what was there.
+    pub fn connect(
+        &mut self,
+        port: usize,
+        device: Box<dyn ControllerDevice>,
+    ) -> Option<Box<dyn ControllerDevice>> {
+        self.ports[port].replace(device)
+    }
+
+    pub fn disconnect(&mut self, port: usize) -> Option<Box<dyn ControllerDevice>> {
+        self.ports[port].take()
+    }
+
+    pub fn connect_expansion(
+        &mut self,
+        device: Box<dyn ControllerDevice>,
+    ) -> Option<Box<dyn ControllerDevice>> {
+        self.expansion.replace(device)
+    }
+
+    pub fn disconnect_expansion(&mut self) -> Option<Box<dyn ControllerDevice>> {
+        self.expansion.take()
+    }
+
+    pub fn port_mut(&mut self, port: usize) -> Option<&mut (dyn ControllerDevice + 'static)> {
+        self.ports[port].as_deref_mut()
+    }
+
+    /// The device in `port` if it is a `T`, e.g. to press buttons on a `Joypad`.
+    pub fn device_mut<T: ControllerDevice>(&mut self, port: usize) -> Option<&mut T> {
+        let device: &mut dyn Any = self.ports[port].as_deref_mut()?;
+        device.downcast_mut()
+    }
+
+    pub fn expansion_mut<T: ControllerDevice>(&mut self) -> Option<&mut T> {
+        let device: &mut dyn Any = self.expansion.as_deref_mut()?;
+        device.downcast_mut()
+    }
+
+    pub fn write(&mut self, data: u8) {
+        for device in self.ports.iter_mut().chain([&mut self.expansion]).flatten() {
+            device.write(data);
+        }
+    }
+
+    /// Read of $4016 (`port` 0) or $4017 (`port` 1). An empty port reads as 0.
+    pub fn read(&mut self, port: usize) -> u8 {
+        let mut data = self.ports[port].as_mut().map_or(0, |device| device.read());
+        if port == 1 {
+            if let Some(expansion) = self.expansion.as_mut() {
+                data |= expansion.read();
+            }
+        }
+        data
+    }
+
+    pub fn peek(&self, port: usize) -> u8 {
+        let mut data = self.ports[port].as_ref().map_or(0, |device| device.peek());
+        if port == 1 {
+            if let Some(expansion) = self.expansion.as_ref() {
+                data |= expansion.peek();
+            }
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::joypad::{Joypad, JoypadButton};
+    use crate::keyboard::{FamilyKeyboard, Key};
+
+    fn strobe(ports: &mut ControllerPorts) {
+        ports.write(1);
+        ports.write(0);
+    }
+
+    #[test]
+    fn buttons_pressed_through_the_ports_reach_the_bus() {
+        let mut ports = ControllerPorts::new();
+        ports.connect(0, Box::new(Joypad::new()));
+        ports
+            .device_mut::<Joypad>(0)
+            .unwrap()
+            .set_button_pressed(JoypadButton::B, true);
+
+        strobe(&mut ports);
+        assert_eq!([ports.read(0), ports.read(0), ports.read(0)], [0, 1, 0]);
+        assert!(ports.device_mut::<FamilyKeyboard>(0).is_none());
+        assert!(ports.device_mut::<Joypad>(1).is_none());
+    }
+
+    #[test]
+    fn expansion_bits_merge_into_port_2() {
+        let mut ports = ControllerPorts::new();
+        let mut pad = Joypad::new();
+        pad.set_buttons(0x01);
+        ports.connect(1, Box::new(pad));
+        ports.connect_expansion(Box::new(FamilyKeyboard::new()));
+        ports
+            .expansion_mut::<FamilyKeyboard>()
+            .unwrap()
+            .set_key_pressed(Key::F8, true);
+
+        // enable the keyboard at row 0, column 0 and latch the pad
+        ports.write(0x05);
+        ports.write(0x04);
+        // pad A on bit 0, F8 pulling keyboard bit 1 low
+        assert_eq!(ports.read(1), 0x1D);
+        assert_eq!(ports.read(0), 0);
+    }
+}
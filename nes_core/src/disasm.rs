@@ -0,0 +1,511 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::labels::{Label, Labels, MemorySpace};
+use crate::opcodes::{self, AddressingMode, Opcode};
+
+/// CDL flag for bytes executed as code (FCEUX / Mesen `.cdl` PRG section).
+pub const CDL_CODE: u8 = 0x01;
+/// CDL flag for bytes read as data.
+pub const CDL_DATA: u8 = 0x02;
+
+const BYTES_PER_LINE: usize = 8;
+const COMMENT_COLUMN: usize = 40;
+
+/// Disassembles PRG ROM into a ca65-style listing.
+///
+/// Without CDL data every byte that starts an official opcode is treated as
+/// code; with it, only bytes logged as code are. Everything else becomes
+/// `.byte` lines. Jump, branch and operand targets inside the image get
+/// their imported label or an `Lxxxx` label, and an image ending at $FFFF
+/// has its vectors written as `.word`s.
+#[derive(Debug, Clone, Copy)]
+pub struct Disassembler<'a> {
+    prg: &'a [u8],
+    base: u16,
+    prg_offset: u32,
+    cdl: Option<&'a [u8]>,
+    labels: Option<&'a Labels>,
+}
+
+enum Item {
+    Instruction(&'static Opcode, u16),
+    Byte(u8),
+    Word(u16),
+}
+
+impl<'a> Disassembler<'a> {
+    /// `prg` is the bytes mapped at CPU address `base`, e.g. a whole NROM
+    /// PRG ROM at $8000 (32 KiB) or $C000 (16 KiB), or a single bank.
+    pub fn new(prg: &'a [u8], base: u16) -> Self {
+        assert!(
+            base as usize + prg.len() <= 0x10000,
+            "PRG image does not fit above ${:04X}",
+            base
+        );
+        Disassembler {
+            prg,
+            base,
+            prg_offset: 0,
+            cdl: None,
+            labels: None,
+        }
+    }
+
+    /// Offset of `prg` into the PRG ROM, which `PrgRom` labels are keyed by.
+    pub fn prg_offset(mut self, offset: u32) -> Self {
+        self.prg_offset = offset;
+        self
+    }
+
+    /// Code/data log for the same bytes as `prg`.
+    pub fn cdl(mut self, cdl: &'a [u8]) -> Self {
+        self.cdl = Some(cdl);
+        self
+    }
+
+    pub fn labels(mut self, labels: &'a Labels) -> Self {
+        self.labels = Some(labels);
+        self
+    }
+
+    pub fn listing(&self) -> String {
+        let items = self.decode();
+        let line_starts: BTreeSet<usize> = items.iter().map(|(offset, _)| *offset).collect();
+
+        let mut targets = BTreeSet::new();
+        for (offset, item) in &items {
+            if let Some(address) = item.target(self.base.wrapping_add(*offset as u16)) {
+                if let Some(offset) = self.offset_of(address) {
+                    targets.insert(offset);
+                }
+            }
+        }
+        let mut symbols = Symbols {
+            disassembler: self,
+            line_starts: &line_starts,
+            targets: &targets,
+            definitions: BTreeMap::new(),
+        };
+
+        let mut body = String::new();
+        let mut index = 0;
+        while index < items.len() {
+            let (offset, item) = &items[index];
+            let address = self.base as usize + offset;
+            for line in self.prg_comment(*offset).lines() {
+                let _ = writeln!(body, "; {}", line);
+            }
+            if let Some(name) = symbols.line_label(*offset) {
+                let _ = writeln!(body, "{}:", name);
+            }
+
+            let text = match item {
+                Item::Instruction(opcode, operand) => {
+                    index += 1;
+                    symbols.instruction(opcode, *operand, address as u16)
+                }
+                Item::Word(value) => {
+                    index += 1;
+                    format!(".word {}", symbols.address(*value))
+                }
+                Item::Byte(_) => {
+                    let mut bytes = Vec::new();
+                    while let Some((offset, Item::Byte(byte))) = items.get(index) {
+                        let annotated = symbols.line_label(*offset).is_some()
+                            || !self.prg_comment(*offset).is_empty();
+                        if bytes.len() == BYTES_PER_LINE || (annotated && !bytes.is_empty()) {
+                            break;
+                        }
+                        bytes.push(format!("${:02X}", byte));
+                        index += 1;
+                    }
+                    format!(".byte {}", bytes.join(","))
+                }
+            };
+            let _ = writeln!(
+                body,
+                "    {:<width$} ; ${:04X}",
+                text,
+                address,
+                width = COMMENT_COLUMN - 5
+            );
+        }
+
+        let mut listing = String::new();
+        for (name, address) in &symbols.definitions {
+            let _ = writeln!(listing, "{} = ${:04X}", name, address);
+        }
+        if !symbols.definitions.is_empty() {
+            listing.push('\n');
+        }
+        let _ = writeln!(listing, ".org ${:04X}", self.base);
+        listing.push_str(&body);
+        listing
+    }
+
+    fn decode(&self) -> Vec<(usize, Item)> {
+        let end = self.base as usize + self.prg.len();
+        // the vectors stay code only if a CDL says something executed there
+        let logged_code = |offset| self.cdl.is_some() && self.is_code(offset);
+        let vectors = if end == 0x10000 && self.prg.len() >= 6 && !logged_code(self.prg.len() - 6) {
+            self.prg.len() - 6
+        } else {
+            self.prg.len()
+        };
+
+        let mut items = Vec::new();
+        let mut offset = 0;
+        while offset < vectors {
+            let byte = self.prg[offset];
+            match opcodes::decode(byte) {
+                Some(opcode) if self.is_code(offset) && offset + opcode.len() <= vectors => {
+                    let operand = match opcode.len() {
+                        1 => 0,
+                        2 => self.prg[offset + 1] as u16,
+                        _ => u16::from_le_bytes([self.prg[offset + 1], self.prg[offset + 2]]),
+                    };
+                    items.push((offset, Item::Instruction(opcode, operand)));
+                    offset += opcode.len();
+                }
+                _ => {
+                    items.push((offset, Item::Byte(byte)));
+                    offset += 1;
+                }
+            }
+        }
+        for offset in (vectors..self.prg.len()).step_by(2) {
+            let value = u16::from_le_bytes([self.prg[offset], self.prg[offset + 1]]);
+            items.push((offset, Item::Word(value)));
+        }
+        items
+    }
+
+    fn is_code(&self, offset: usize) -> bool {
+        match self.cdl {
+            Some(cdl) => cdl.get(offset).is_some_and(|flags| flags & CDL_CODE != 0),
+            None => true,
+        }
+    }
+
+    fn offset_of(&self, address: u16) -> Option<usize> {
+        let offset = (address as usize).checked_sub(self.base as usize)?;
+        (offset < self.prg.len()).then_some(offset)
+    }
+
+    fn prg_label(&self, offset: usize) -> Option<(&'a Label, u32)> {
+        self.labels?
+            .get_named(MemorySpace::PrgRom, self.prg_offset + offset as u32)
+    }
+
+    /// Comment of the label starting at `offset`, named or not.
+    fn prg_comment(&self, offset: usize) -> &'a str {
+        let label = self
+            .labels
+            .and_then(|labels| labels.get(MemorySpace::PrgRom, self.prg_offset + offset as u32));
+        match label {
+            Some((label, 0)) => &label.comment,
+            _ => "",
+        }
+    }
+}
+
+impl Item {
+    /// The address an instruction or vector at `address` refers to, if any.
+    fn target(&self, address: u16) -> Option<u16> {
+        match self {
+            Item::Instruction(opcode, operand) => match opcode.mode {
+                AddressingMode::Absolute
+                | AddressingMode::AbsoluteX
+                | AddressingMode::AbsoluteY
+                | AddressingMode::Indirect => Some(*operand),
+                AddressingMode::Relative => Some(branch_target(address, *operand)),
+                _ => None,
+            },
+            Item::Word(value) => Some(*value),
+            Item::Byte(_) => None,
+        }
+    }
+}
+
+struct Symbols<'d, 'a> {
+    disassembler: &'d Disassembler<'a>,
+    line_starts: &'d BTreeSet<usize>,
+    targets: &'d BTreeSet<usize>,
+    /// Labels outside the image, written out as `name = $addr` up front.
+    definitions: BTreeMap<String, u16>,
+}
+
+impl Symbols<'_, '_> {
+    /// Label to emit at the start of the line at `offset`.
+    fn line_label(&self, offset: usize) -> Option<String> {
+        match self.disassembler.prg_label(offset) {
+            Some((label, 0)) => return Some(label.name.clone()),
+            // inside an imported label, referred to as `name+offset`
+            Some(_) => return None,
+            None => {}
+        }
+        self.targets
+            .contains(&offset)
+            .then(|| format!("L{:04X}", self.disassembler.base as usize + offset))
+    }
+
+    /// Symbolic form of `address`, falling back to hex.
+    fn address(&mut self, address: u16) -> String {
+        self.symbol(address)
+            .unwrap_or_else(|| format!("${:04X}", address))
+    }
+
+    fn zero_page(&mut self, address: u8) -> String {
+        self.symbol(address as u16)
+            .unwrap_or_else(|| format!("${:02X}", address))
+    }
+
+    fn symbol(&mut self, address: u16) -> Option<String> {
+        if let Some(offset) = self.disassembler.offset_of(address) {
+            // a label that starts mid-instruction never gets a line to sit on
+            let (start, within) = match self.disassembler.prg_label(offset) {
+                Some((_, within)) => (offset.checked_sub(within as usize)?, within),
+                None => (offset, 0),
+            };
+            if !self.line_starts.contains(&start) {
+                return None;
+            }
+            let name = self.line_label(start)?;
+            return Some(match within {
+                0 => name,
+                within => format!("{}+{}", name, within),
+            });
+        }
+
+        let labels = self.disassembler.labels?;
+        // a mirror of internal RAM would define the label at the mirrored address
+        let mirrored = (0x0800..0x2000).contains(&address);
+        if mirrored && labels.name(MemorySpace::Cpu, address as u32).is_none() {
+            return None;
+        }
+        let name = labels.cpu_name(address)?;
+        let (base, within) = match name.rsplit_once('+') {
+            Some((base, within)) => (base, within.parse::<u16>().ok()?),
+            None => (name.as_str(), 0),
+        };
+        let start = address - within;
+        match self.definitions.get(base) {
+            // the same name in two memory spaces can only be defined once
+            Some(defined) if *defined != start => None,
+            _ => {
+                self.definitions.insert(base.into(), start);
+                Some(name)
+            }
+        }
+    }
+
+    fn instruction(&mut self, opcode: &Opcode, operand: u16, address: u16) -> String {
+        // ca65 picks zero page for small values unless told otherwise
+        let absolute = |symbols: &mut Self| {
+            let text = symbols.address(operand);
+            if operand < 0x100 {
+                format!("a:{}", text)
+            } else {
+                text
+            }
+        };
+        let operand_text = match opcode.mode {
+            AddressingMode::Implied => String::new(),
+            AddressingMode::Accumulator => String::from("A"),
+            AddressingMode::Immediate => format!("#${:02X}", operand),
+            AddressingMode::ZeroPage => self.zero_page(operand as u8),
+            AddressingMode::ZeroPageX => format!("{},X", self.zero_page(operand as u8)),
+            AddressingMode::ZeroPageY => format!("{},Y", self.zero_page(operand as u8)),
+            AddressingMode::Absolute => absolute(self),
+            AddressingMode::AbsoluteX => format!("{},X", absolute(self)),
+            AddressingMode::AbsoluteY => format!("{},Y", absolute(self)),
+            AddressingMode::Indirect => format!("({})", self.address(operand)),
+            AddressingMode::IndirectX => format!("({},X)", self.zero_page(operand as u8)),
+            AddressingMode::IndirectY => format!("({}),Y", self.zero_page(operand as u8)),
+            AddressingMode::Relative => self.address(branch_target(address, operand)),
+        };
+        if operand_text.is_empty() {
+            String::from(opcode.mnemonic)
+        } else {
+            format!("{} {}", opcode.mnemonic, operand_text)
+        }
+    }
+}
+
+fn branch_target(address: u16, operand: u16) -> u16 {
+    address
+        .wrapping_add(2)
+        .wrapping_add(operand as u8 as i8 as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::assemble_at;
+    use alloc::vec;
+
+    /// A 16 KiB NROM image at $C000 holding `source`, with the given vectors.
+    fn image(source: &str, vectors: [u16; 3]) -> Vec<u8> {
+        let mut prg = assemble_at(0xC000, source).unwrap();
+        prg.resize(0x4000 - 6, 0xFF);
+        prg.extend(vectors.into_iter().flat_map(u16::to_le_bytes));
+        prg
+    }
+
+    fn lines(listing: &str) -> Vec<&str> {
+        listing
+            .lines()
+            .map(|line| line.split(" ;").next().unwrap().trim_end())
+            .collect()
+    }
+
+    #[test]
+    fn labels_branches_and_vectors_without_cdl() {
+        let prg = image(
+            "reset: LDX #$00; loop: DEX; BNE loop; JMP reset",
+            [0xC000, 0xC000, 0xC002],
+        );
+        let listing = Disassembler::new(&prg, 0xC000).listing();
+        let lines = lines(&listing);
+        assert_eq!(
+            lines[..8],
+            [
+                ".org $C000",
+                "LC000:",
+                "    LDX #$00",
+                "LC002:",
+                "    DEX",
+                "    BNE LC002",
+                "    JMP LC000",
+                "    .byte $FF,$FF,$FF,$FF,$FF,$FF,$FF,$FF",
+            ]
+        );
+        assert_eq!(
+            lines[lines.len() - 3..],
+            ["    .word LC000", "    .word LC000", "    .word LC002"]
+        );
+    }
+
+    #[test]
+    fn cdl_marks_data_and_can_keep_vectors_as_code() {
+        let prg = image("RTS; .byte $A9, $00", [0xC000; 3]);
+        let mut cdl = vec![CDL_DATA; prg.len()];
+        cdl[0] = CDL_CODE;
+        let listing = Disassembler::new(&prg, 0xC000).cdl(&cdl).listing();
+        let lines = lines(&listing);
+        assert_eq!(lines[2], "    RTS");
+        assert_eq!(lines[3], "    .byte $A9,$00,$FF,$FF,$FF,$FF,$FF,$FF");
+        assert_eq!(lines[lines.len() - 1], "    .word LC000");
+
+        cdl[0x3FFA..].fill(CDL_CODE);
+        let listing = Disassembler::new(&prg, 0xC000).cdl(&cdl).listing();
+        assert!(!listing.contains(".word"));
+    }
+
+    #[test]
+    fn imported_labels_from_several_spaces() {
+        let mut labels = Labels::new();
+        labels
+            .import_mlb(
+                "G:2000:PPUCTRL\n\
+                 R:0010:counter\n\
+                 R:0200-02FF:Oam\n\
+                 P:0000:Reset:power on\n\
+                 P:0010-0011:Table\n",
+            )
+            .unwrap();
+        let mut prg = image(
+            "LDA $0010; STA $2000; DEC $10; STA $0204,X; LDA $C011; RTS",
+            [0xC000; 3],
+        );
+        prg[0x10..0x12].copy_from_slice(&[1, 2]);
+        let mut cdl = vec![CDL_DATA; prg.len()];
+        cdl[..0x10].fill(CDL_CODE);
+
+        let listing = Disassembler::new(&prg, 0xC000)
+            .cdl(&cdl)
+            .labels(&labels)
+            .listing();
+        assert_eq!(
+            lines(&listing)[..16],
+            [
+                "Oam = $0200",
+                "PPUCTRL = $2000",
+                "counter = $0010",
+                "",
+                ".org $C000",
+                "; power on",
+                "Reset:",
+                "    LDA a:counter",
+                "    STA PPUCTRL",
+                "    DEC counter",
+                "    STA Oam+4,X",
+                "    LDA Table+1",
+                "    RTS",
+                "    .byte $FF",
+                "Table:",
+                "    .byte $01,$02,$FF,$FF,$FF,$FF,$FF,$FF",
+            ]
+        );
+    }
+
+    #[test]
+    fn mirrored_and_ambiguous_ram_labels_stay_hex() {
+        let mut labels = Labels::new();
+        labels
+            .import_mlb("R:0010:counter\nW:0000:tmp\nS:0100:tmp\n")
+            .unwrap();
+        let prg = image(
+            "LDA $0010; LDA $0810; LDA $6000; LDA $6100; RTS",
+            [0xC000; 3],
+        );
+        let listing = Disassembler::new(&prg, 0xC000).labels(&labels).listing();
+        assert_eq!(
+            lines(&listing)[..10],
+            [
+                "counter = $0010",
+                "tmp = $6000",
+                "",
+                ".org $C000",
+                "LC000:",
+                "    LDA a:counter",
+                "    LDA $0810",
+                "    LDA tmp",
+                "    LDA $6100",
+                "    RTS",
+            ]
+        );
+    }
+
+    #[test]
+    fn comment_only_labels_reach_the_listing() {
+        let mut labels = Labels::new();
+        labels
+            .import_mlb("P:0000::note\nP:0001-0002:Table\nP:0002::inside\n")
+            .unwrap();
+        let prg = image("RTS; .byte 1, 2", [0xC000; 3]);
+        let mut cdl = vec![CDL_DATA; prg.len()];
+        cdl[0] = CDL_CODE;
+        let listing = Disassembler::new(&prg, 0xC000)
+            .cdl(&cdl)
+            .labels(&labels)
+            .listing();
+        assert_eq!(
+            lines(&listing)[..8],
+            [
+                ".org $C000",
+                "; note",
+                "LC000:",
+                "    RTS",
+                "Table:",
+                "    .byte $01",
+                "; inside",
+                "    .byte $02,$FF,$FF,$FF,$FF,$FF,$FF,$FF",
+            ]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use core::fmt;
+
+/// Formats memory as `0200: 00 11 22 ...  |."...|` lines of 16 bytes.
+///
+/// `base` is the address of the first byte, used for the left-hand column.
+pub fn hexdump(data: &[u8], base: u32) -> HexDump<'_> {
+    HexDump { data, base }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HexDump<'a> {
+    data: &'a [u8],
+    base: u32,
+}
+
+impl fmt::Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const WIDTH: usize = 16;
+
+        let last = self.base as u64 + self.data.len().saturating_sub(1) as u64;
+        let digits = if last > 0xFFFF { 8 } else { 4 };
+        for (row, chunk) in self.data.chunks(WIDTH).enumerate() {
+            let address = self.base as u64 + (row * WIDTH) as u64;
+            write!(f, "{:0width$X}:", address, width = digits)?;
+            for byte in chunk {
+                write!(f, " {:02X}", byte)?;
+            }
+            for _ in chunk.len()..WIDTH {
+                write!(f, "   ")?;
+            }
+            write!(f, "  |")?;
+            for byte in chunk {
+                let c = if byte.is_ascii_graphic() || *byte == b' ' {
+                    *byte as char
+                } else {
+                    '.'
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f, "|")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn partial_last_row_is_padded() {
+        let data: [u8; 18] = core::array::from_fn(|i| i as u8);
+        assert_eq!(
+            hexdump(&data, 0x0200).to_string(),
+            "0200: 00 01 02 03 04 05 06 07 08 09 0A 0B 0C 0D 0E 0F  |................|\n\
+             0210: 10 11                                            |..|\n"
+        );
+    }
+
+    #[test]
+    fn ascii_column_shows_printable_bytes_and_spaces() {
+        assert_eq!(
+            hexdump(b"Hi !~\x00\x7F\x80\xFF", 0).to_string(),
+            "0000: 48 69 20 21 7E 00 7F 80 FF                       |Hi !~....|\n"
+        );
+    }
+
+    #[test]
+    fn addresses_widen_above_ffff() {
+        let lines = |data: &[u8], base| {
+            hexdump(data, base)
+                .to_string()
+                .lines()
+                .map(|line| line[..line.find(':').unwrap()].to_string())
+                .collect::<Vec<_>>()
+        };
+        // the width is set by the last byte, so every row lines up
+        assert_eq!(lines(&[0; 16], 0xFFF0), ["FFF0"]);
+        assert_eq!(lines(&[0; 24], 0xFFF8), ["0000FFF8", "00010008"]);
+        assert_eq!(lines(&[0; 1], 0x0012_3456), ["00123456"]);
+        assert_eq!(hexdump(&[], 0x0010_0000).to_string(), "");
+    }
+}
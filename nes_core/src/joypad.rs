@@ -0,0 +1,186 @@
+use crate::controller::ControllerDevice;
+
+/// Buttons in the order the controller shifts them out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoypadButton {
+    A,
+    B,
+    Select,
+    Start,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl JoypadButton {
+    pub fn mask(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Standard controller: a latch plus an 8-bit shift register read through $4016/$4017.
+#[derive(Debug, Clone, Default)]
+pub struct Joypad {
+    strobe: bool,
+    button_index: u8,
+    button_status: u8,
+}
+
+impl Joypad {
+    pub fn new() -> Self {
+        Joypad::default()
+    }
+
+    /// Replaces the state of all buttons, bit 0 = A through bit 7 = Right.
+    pub fn set_buttons(&mut self, buttons: u8) {
+        self.button_status = buttons;
+    }
+
+    pub fn set_button_pressed(&mut self, button: JoypadButton, pressed: bool) {
+        if pressed {
+            self.button_status |= button.mask();
+        } else {
+            self.button_status &= !button.mask();
+        }
+    }
+}
+
+impl ControllerDevice for Joypad {
+    /// Bit 0 high keeps reloading the buttons.
+    fn write(&mut self, data: u8) {
+        self.strobe = data & 1 == 1;
+        if self.strobe {
+            self.button_index = 0;
+        }
+    }
+
+    fn read(&mut self) -> u8 {
+        let response = self.peek();
+        if !self.strobe && self.button_index <= 7 {
+            self.button_index += 1;
+        }
+        response
+    }
+
+    fn peek(&self) -> u8 {
+        if self.button_index > 7 {
+            return 1;
+        }
+        (self.button_status >> self.button_index) & 1
+    }
+}
+
+/// One port's half of a Four Score multitap in four-player mode.
+///
+/// Connect `FourScore::new(0)` to $4016 and `FourScore::new(1)` to $4017.
+/// Each returns 24 bits: the pad plugged in front (pad 1 or 2), the pad behind
+/// it (pad 3 or 4), then a signature byte that is all 0 except for a 1 on read
+/// 20 ($4016) or read 19 ($4017). Later reads return 1.
+#[derive(Debug, Clone, Default)]
+pub struct FourScore {
+    pads: [u8; 2],
+    signature: u8,
+    strobe: bool,
+    shift: u32,
+}
+
+impl FourScore {
+    // shifted out LSB first like the pads: bit 3 is read 20, bit 2 read 19
+    const SIGNATURES: [u8; 2] = [0x08, 0x04];
+
+    pub fn new(port: usize) -> Self {
+        FourScore {
+            signature: Self::SIGNATURES[port],
+            ..FourScore::default()
+        }
+    }
+
+    /// `pad` 0 is the front pad (1 or 2), 1 the one behind it (3 or 4);
+    /// buttons as in `Joypad::set_buttons`.
+    pub fn set_buttons(&mut self, pad: usize, buttons: u8) {
+        self.pads[pad] = buttons;
+    }
+
+    fn reload(&mut self) {
+        self.shift =
+            self.pads[0] as u32 | (self.pads[1] as u32) << 8 | (self.signature as u32) << 16;
+    }
+}
+
+impl ControllerDevice for FourScore {
+    fn write(&mut self, data: u8) {
+        // the pads stay latched for as long as the strobe is high
+        if self.strobe || data & 1 == 1 {
+            self.reload();
+        }
+        self.strobe = data & 1 == 1;
+    }
+
+    fn read(&mut self) -> u8 {
+        let response = self.peek();
+        if !self.strobe {
+            self.shift = (self.shift >> 1) | (1 << 23);
+        }
+        response
+    }
+
+    fn peek(&self) -> u8 {
+        if self.strobe {
+            return self.pads[0] & 1;
+        }
+        (self.shift & 1) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn read_bits(device: &mut impl ControllerDevice, count: usize) -> Vec<u8> {
+        device.write(1);
+        device.write(0);
+        (0..count).map(|_| device.read()).collect()
+    }
+
+    #[test]
+    fn joypad_shifts_buttons_then_ones() {
+        let mut pad = Joypad::new();
+        pad.set_button_pressed(JoypadButton::A, true);
+        pad.set_button_pressed(JoypadButton::Start, true);
+        pad.set_button_pressed(JoypadButton::Right, true);
+        assert_eq!(read_bits(&mut pad, 10), [1, 0, 0, 1, 0, 0, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn joypad_strobe_high_keeps_returning_a() {
+        let mut pad = Joypad::new();
+        pad.set_buttons(0x01);
+        pad.write(1);
+        assert_eq!([pad.read(), pad.read()], [1, 1]);
+    }
+
+    #[test]
+    fn four_score_reports_both_pads_and_signature() {
+        let mut port0 = FourScore::new(0);
+        port0.set_buttons(0, 0x01);
+        port0.set_buttons(1, 0x80);
+        let mut expected = [0; 26];
+        expected[0] = 1; // pad 1 A
+        expected[15] = 1; // pad 3 Right
+        expected[19] = 1; // signature, read 20
+        expected[24] = 1;
+        expected[25] = 1;
+        assert_eq!(read_bits(&mut port0, 26), expected);
+
+        let mut port1 = FourScore::new(1);
+        port1.set_buttons(0, 0x02);
+        let mut expected = [0; 26];
+        expected[1] = 1; // pad 2 B
+        expected[18] = 1; // signature, read 19
+        expected[24] = 1;
+        expected[25] = 1;
+        assert_eq!(read_bits(&mut port1, 26), expected);
+    }
+}
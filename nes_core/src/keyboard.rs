@@ -0,0 +1,269 @@
+use crate::controller::ControllerDevice;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    Num1,
+    Num2,
+    Num3,
+    Num4,
+    Num5,
+    Num6,
+    Num7,
+    Num8,
+    Num9,
+    Num0,
+    Minus,
+    Caret,
+    Yen,
+    Stop,
+    Escape,
+    Q,
+    W,
+    E,
+    R,
+    T,
+    Y,
+    U,
+    I,
+    O,
+    P,
+    At,
+    LeftBracket,
+    Return,
+    Ctrl,
+    A,
+    S,
+    D,
+    F,
+    G,
+    H,
+    J,
+    K,
+    L,
+    Semicolon,
+    Colon,
+    RightBracket,
+    Kana,
+    LeftShift,
+    Z,
+    X,
+    C,
+    V,
+    B,
+    N,
+    M,
+    Comma,
+    Period,
+    Slash,
+    Underscore,
+    RightShift,
+    Graph,
+    Space,
+    ClrHome,
+    Insert,
+    Delete,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+const ROWS: usize = 9;
+
+/// Key at [row][column][bit], where bit 0-3 is read back on $4017 bits 1-4.
+const MATRIX: [[[Key; 4]; 2]; ROWS] = {
+    use Key::*;
+    [
+        [
+            [F8, Return, LeftBracket, RightBracket],
+            [Kana, RightShift, Yen, Stop],
+        ],
+        [
+            [F7, At, Colon, Semicolon],
+            [Underscore, Slash, Minus, Caret],
+        ],
+        [[F6, O, L, K], [Period, Comma, P, Num0]],
+        [[F5, I, U, J], [M, N, Num9, Num8]],
+        [[F4, Y, G, H], [B, V, Num7, Num6]],
+        [[F3, T, R, D], [F, C, Num5, Num4]],
+        [[F2, W, S, A], [X, Z, E, Num3]],
+        [[F1, Escape, Q, Ctrl], [LeftShift, Graph, Num1, Num2]],
+        [[ClrHome, Up, Right, Left], [Down, Space, Delete, Insert]],
+    ]
+};
+
+/// Family BASIC keyboard on the expansion port.
+///
+/// $4016 writes: bit 0 resets the row counter, bit 1 selects the column (a
+/// 1 -> 0 transition advances to the next row), bit 2 enables the matrix.
+/// $4017 reads return the selected half-row in bits 1-4, 0 meaning pressed.
+///
+/// Only the data recorder's output is exposed, through `tape_out`; its input
+/// arrives on $4016 bit 1, outside the expansion bits, and reads as silence.
+#[derive(Debug, Clone, Default)]
+pub struct FamilyKeyboard {
+    enabled: bool,
+    tape_out: bool,
+    row: usize,
+    column: usize,
+    pressed: [[u8; 2]; ROWS],
+}
+
+impl FamilyKeyboard {
+    pub fn new() -> Self {
+        FamilyKeyboard::default()
+    }
+
+    pub fn set_key_pressed(&mut self, key: Key, pressed: bool) {
+        let (row, column, bit) = position(key);
+        if pressed {
+            self.pressed[row][column] |= 1 << bit;
+        } else {
+            self.pressed[row][column] &= !(1 << bit);
+        }
+    }
+
+    /// Level sent to the data recorder, bit 0 of the last $4016 write.
+    pub fn tape_out(&self) -> bool {
+        self.tape_out
+    }
+}
+
+impl ControllerDevice for FamilyKeyboard {
+    fn write(&mut self, data: u8) {
+        let previous_column = self.column;
+        self.column = ((data >> 1) & 1) as usize;
+        self.enabled = data & 0x04 != 0;
+        self.tape_out = data & 0x01 != 0;
+        if self.enabled {
+            if previous_column == 1 && self.column == 0 {
+                self.row = (self.row + 1) % (ROWS + 1);
+            }
+            if data & 0x01 != 0 {
+                self.row = 0;
+            }
+        }
+    }
+
+    /// Bits 1-4 of a $4017 read.
+    fn read(&mut self) -> u8 {
+        self.peek()
+    }
+
+    fn peek(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+        match self.pressed.get(self.row) {
+            Some(row) => (!row[self.column] << 1) & 0x1E,
+            // past the last row nothing is ever pressed
+            None => 0x1E,
+        }
+    }
+}
+
+fn position(key: Key) -> (usize, usize, usize) {
+    for (row, columns) in MATRIX.iter().enumerate() {
+        for (column, keys) in columns.iter().enumerate() {
+            if let Some(bit) = keys.iter().position(|candidate| *candidate == key) {
+                return (row, column, bit);
+            }
+        }
+    }
+    unreachable!("every key is in the matrix")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    const RESET: u8 = 0x05;
+    const COLUMN_0: u8 = 0x04;
+    const COLUMN_1: u8 = 0x06;
+
+    #[test]
+    fn column_1_to_0_advances_the_row() {
+        let mut keyboard = FamilyKeyboard::new();
+        keyboard.set_key_pressed(Key::Return, true);
+        keyboard.set_key_pressed(Key::At, true);
+
+        keyboard.write(RESET);
+        assert_eq!(keyboard.read(), 0x1A);
+        keyboard.write(COLUMN_1);
+        assert_eq!(keyboard.read(), 0x1E);
+        // rewriting the same column does not move on
+        keyboard.write(COLUMN_1);
+        keyboard.write(COLUMN_0);
+        assert_eq!(keyboard.read(), 0x1A);
+        keyboard.write(COLUMN_0);
+        assert_eq!(keyboard.read(), 0x1A);
+
+        keyboard.write(RESET);
+        keyboard.set_key_pressed(Key::Return, false);
+        assert_eq!(keyboard.read(), 0x1E);
+    }
+
+    #[test]
+    fn disabled_keyboard_reads_0() {
+        let mut keyboard = FamilyKeyboard::new();
+        keyboard.set_key_pressed(Key::F8, true);
+        keyboard.write(0x01);
+        assert_eq!(keyboard.read(), 0);
+        keyboard.write(RESET);
+        assert_eq!(keyboard.read(), 0x1C);
+        // column changes while disabled do not advance the row
+        keyboard.write(0x02);
+        keyboard.write(0x00);
+        keyboard.write(COLUMN_0);
+        assert_eq!(keyboard.read(), 0x1C);
+    }
+
+    #[test]
+    fn full_scan_finds_each_key_once() {
+        let mut keyboard = FamilyKeyboard::new();
+        keyboard.set_key_pressed(Key::Space, true);
+        keyboard.set_key_pressed(Key::Num3, true);
+
+        keyboard.write(RESET);
+        let mut pressed = Vec::new();
+        for (row, keys) in MATRIX.iter().enumerate() {
+            for (column, data) in [(0, COLUMN_0), (1, COLUMN_1)] {
+                keyboard.write(data);
+                let bits = !keyboard.read() & 0x1E;
+                for (bit, key) in keys[column].iter().enumerate() {
+                    if bits & (2 << bit) != 0 {
+                        pressed.push((row, column, *key));
+                    }
+                }
+            }
+        }
+        assert_eq!(pressed, [(6, 1, Key::Num3), (8, 1, Key::Space)]);
+
+        // the 10th row is past the matrix, then the counter wraps to row 0
+        keyboard.write(COLUMN_0);
+        assert_eq!(keyboard.read(), 0x1E);
+        keyboard.write(COLUMN_1);
+        assert_eq!(keyboard.read(), 0x1E);
+        keyboard.write(COLUMN_0);
+        keyboard.set_key_pressed(Key::F8, true);
+        assert_eq!(keyboard.read(), 0x1C);
+    }
+
+    #[test]
+    fn tape_out_follows_bit_0() {
+        let mut keyboard = FamilyKeyboard::new();
+        keyboard.write(0x01);
+        assert!(keyboard.tape_out());
+        keyboard.write(COLUMN_0);
+        assert!(!keyboard.tape_out());
+    }
+}
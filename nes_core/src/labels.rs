@@ -0,0 +1,308 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MemorySpace {
+    /// CPU bus addresses, e.g. PPU/APU registers.
+    Cpu,
+    /// Offset into the 2 KiB internal RAM.
+    InternalRam,
+    /// Offset into PRG ROM.
+    PrgRom,
+    /// Offset into cartridge work RAM.
+    WorkRam,
+    /// Offset into battery-backed save RAM.
+    SaveRam,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub name: String,
+    pub comment: String,
+    /// Number of bytes covered, 1 for a plain label.
+    pub length: u32,
+}
+
+/// Which FCEUX `.nl` file is being imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NlFile {
+    /// `<rom>.ram.nl`, CPU addresses below $8000.
+    Ram,
+    /// `<rom>.<bank>.nl`, labels for the given 16 KiB PRG bank.
+    Bank(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelError {
+    /// 1-based line of the label file.
+    pub line: usize,
+}
+
+impl fmt::Display for LabelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: malformed label entry", self.line)
+    }
+}
+
+impl core::error::Error for LabelError {}
+
+/// Address to label/comment table shared by the debugging tools.
+#[derive(Debug, Clone, Default)]
+pub struct Labels {
+    entries: BTreeMap<(MemorySpace, u32), Label>,
+}
+
+impl Labels {
+    pub fn new() -> Self {
+        Labels::default()
+    }
+
+    pub fn insert(&mut self, space: MemorySpace, address: u32, label: Label) {
+        self.entries.insert((space, address), label);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The label covering `address`, with the offset of `address` into it.
+    /// Where ranges overlap, the one starting closest to `address` wins.
+    pub fn get(&self, space: MemorySpace, address: u32) -> Option<(&Label, u32)> {
+        self.covering(space, address).next()
+    }
+
+    /// Like `get`, but skips comment-only entries.
+    pub fn get_named(&self, space: MemorySpace, address: u32) -> Option<(&Label, u32)> {
+        self.covering(space, address)
+            .find(|(label, _)| !label.name.is_empty())
+    }
+
+    fn covering(
+        &self,
+        space: MemorySpace,
+        address: u32,
+    ) -> impl Iterator<Item = (&Label, u32)> + '_ {
+        self.entries
+            .range((space, 0)..=(space, address))
+            .rev()
+            .map(move |((_, start), label)| (label, address - start))
+            .filter(|(label, offset)| *offset < label.length.max(1))
+    }
+
+    /// `name` or `name+offset` for `address`, skipping comment-only entries.
+    pub fn name(&self, space: MemorySpace, address: u32) -> Option<String> {
+        let (label, offset) = self.get_named(space, address)?;
+        match offset {
+            0 => Some(label.name.clone()),
+            offset => Some(format!("{}+{}", label.name, offset)),
+        }
+    }
+
+    /// Looks up a CPU address below $8000; PRG addresses depend on the mapper,
+    /// so callers translate those to a PRG ROM offset themselves.
+    pub fn cpu_name(&self, address: u16) -> Option<String> {
+        let address = address as u32;
+        self.name(MemorySpace::Cpu, address)
+            .or_else(|| match address {
+                0x0000..=0x1FFF => self.name(MemorySpace::InternalRam, address & 0x07FF),
+                0x6000..=0x7FFF => self
+                    .name(MemorySpace::SaveRam, address - 0x6000)
+                    .or_else(|| self.name(MemorySpace::WorkRam, address - 0x6000)),
+                _ => None,
+            })
+    }
+
+    /// Imports a Mesen `.mlb` file, returning the number of labels read.
+    ///
+    /// Lines look like `P:C000:Reset:comment` or `R:0200-02FF:Oam`, with the
+    /// single-letter types of Mesen or the `Nes*` names of Mesen 2. Entries for
+    /// other memory types are skipped.
+    pub fn import_mlb(&mut self, text: &str) -> Result<usize, LabelError> {
+        let mut count = 0;
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            let error = LabelError { line: index + 1 };
+            let mut fields = line.splitn(4, ':');
+            let kind = fields.next().ok_or(error.clone())?;
+            let range = fields.next().ok_or(error.clone())?;
+            let name = fields.next().ok_or(error.clone())?;
+            let comment = fields.next().unwrap_or("").replace("\\n", "\n");
+
+            let space = match kind {
+                "P" | "NesPrgRom" => MemorySpace::PrgRom,
+                "R" | "NesInternalRam" => MemorySpace::InternalRam,
+                "W" | "NesWorkRam" => MemorySpace::WorkRam,
+                "S" | "NesSaveRam" => MemorySpace::SaveRam,
+                "G" | "NesMemory" => MemorySpace::Cpu,
+                _ => continue,
+            };
+            let (start, end) = match range.split_once('-') {
+                Some((start, end)) => (parse_hex(start), parse_hex(end)),
+                None => (parse_hex(range), parse_hex(range)),
+            };
+            let (start, end) = match (start, end) {
+                (Some(start), Some(end)) if end >= start => (start, end),
+                _ => return Err(error),
+            };
+            self.insert(
+                space,
+                start,
+                Label {
+                    name: name.to_string(),
+                    comment,
+                    length: end - start + 1,
+                },
+            );
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Imports an FCEUX `.nl` file, returning the number of labels read.
+    ///
+    /// Lines look like `$C000#Reset#comment` or `$0200/100#Oam#` for a range
+    /// (length in hex); a line starting with `\` continues the previous comment.
+    pub fn import_nl(&mut self, text: &str, file: NlFile) -> Result<usize, LabelError> {
+        let mut count = 0;
+        let mut last = None;
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            let error = LabelError { line: index + 1 };
+            if let Some(more) = line.strip_prefix('\\') {
+                let key = last.ok_or(error)?;
+                if let Some(label) = self.entries.get_mut(&key) {
+                    label.comment.push('\n');
+                    label.comment.push_str(more);
+                }
+                continue;
+            }
+
+            let mut fields = line.splitn(3, '#');
+            let address = fields.next().and_then(|field| field.strip_prefix('$'));
+            let name = fields.next().ok_or(error.clone())?;
+            let comment = fields.next().unwrap_or("").trim_end_matches('#');
+            let (address, length) = match address.map(|field| field.split_once('/')) {
+                Some(Some((address, length))) => (parse_hex(address), parse_hex(length)),
+                Some(None) => (address.and_then(parse_hex), Some(1)),
+                None => (None, None),
+            };
+            let (address, length) = match (address, length) {
+                (Some(address), Some(length)) if length > 0 => (address, length),
+                _ => return Err(error),
+            };
+
+            let key = match file {
+                NlFile::Ram if address < 0x2000 => (MemorySpace::InternalRam, address & 0x07FF),
+                NlFile::Ram => (MemorySpace::Cpu, address),
+                NlFile::Bank(bank) => (MemorySpace::PrgRom, bank * 0x4000 + (address & 0x3FFF)),
+            };
+            self.insert(
+                key.0,
+                key.1,
+                Label {
+                    name: name.to_string(),
+                    comment: comment.to_string(),
+                    length,
+                },
+            );
+            last = Some(key);
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+fn parse_hex(text: &str) -> Option<u32> {
+    u32::from_str_radix(text.trim(), 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookups_stay_within_their_memory_space() {
+        let mut labels = Labels::new();
+        labels
+            .import_mlb("G:8000:Reg\nR:0300:buffer\nP:0100:Reset\n")
+            .unwrap();
+        assert_eq!(labels.get(MemorySpace::InternalRam, 0x10), None);
+        assert_eq!(labels.get(MemorySpace::PrgRom, 0x80), None);
+        assert_eq!(labels.get(MemorySpace::SaveRam, 0xFFFF), None);
+        assert_eq!(
+            labels.name(MemorySpace::PrgRom, 0x100).as_deref(),
+            Some("Reset")
+        );
+        assert_eq!(labels.cpu_name(0x0B00).as_deref(), Some("buffer"));
+    }
+
+    #[test]
+    fn overlapping_ranges_resolve_to_the_innermost_label() {
+        let mut labels = Labels::new();
+        labels.import_mlb("R:0200-02FF:Oam\nR:0210:x\n").unwrap();
+        assert_eq!(labels.cpu_name(0x0210).as_deref(), Some("x"));
+        assert_eq!(labels.cpu_name(0x0220).as_deref(), Some("Oam+32"));
+        assert_eq!(labels.cpu_name(0x0300), None);
+    }
+
+    #[test]
+    fn imports_mlb() {
+        let mut labels = Labels::new();
+        let count = labels
+            .import_mlb(
+                "P:C000:Reset:entry\\npoint\r\n\
+                 NesWorkRam:0010-0011:ptr:\n\
+                 S:0000::save slot\n\
+                 X:0000:skipped\n",
+            )
+            .unwrap();
+        assert_eq!(count, 3);
+        let (reset, offset) = labels.get(MemorySpace::PrgRom, 0xC000).unwrap();
+        assert_eq!(
+            (reset.name.as_str(), reset.comment.as_str(), offset),
+            ("Reset", "entry\npoint", 0)
+        );
+        assert_eq!(labels.cpu_name(0x6011).as_deref(), Some("ptr+1"));
+        // comment-only entries have no name to show
+        assert_eq!(labels.cpu_name(0x6000), None);
+        assert_eq!(labels.import_mlb("P:zz:Bad"), Err(LabelError { line: 1 }));
+    }
+
+    #[test]
+    fn imports_nl() {
+        let mut labels = Labels::new();
+        let count = labels
+            .import_nl(
+                "$0010#counter#frames\n\\since reset\n$0200/100#Oam#\n$2000#PPUCTRL#\n",
+                NlFile::Ram,
+            )
+            .unwrap();
+        assert_eq!(count, 3);
+        let (counter, _) = labels.get(MemorySpace::InternalRam, 0x10).unwrap();
+        assert_eq!(counter.comment, "frames\nsince reset");
+        assert_eq!(labels.cpu_name(0x02FF).as_deref(), Some("Oam+255"));
+        assert_eq!(labels.cpu_name(0x2000).as_deref(), Some("PPUCTRL"));
+
+        labels.import_nl("$C123#Main#\n", NlFile::Bank(3)).unwrap();
+        assert_eq!(
+            labels.name(MemorySpace::PrgRom, 0xC123).as_deref(),
+            Some("Main")
+        );
+        assert_eq!(
+            labels.import_nl("C000#NoDollar#", NlFile::Ram),
+            Err(LabelError { line: 1 })
+        );
+    }
+}
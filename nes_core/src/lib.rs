@@ -1 +1,35 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "debugger")]
+pub mod asm;
+#[cfg(feature = "std")]
+pub mod battery;
+pub mod cheat;
+pub mod controller;
+pub mod crc32;
+#[cfg(feature = "debugger")]
+pub mod disasm;
+#[cfg(feature = "debugger")]
+pub mod hexdump;
+pub mod joypad;
+pub mod keyboard;
+#[cfg(feature = "debugger")]
+pub mod labels;
+pub mod movie;
+#[cfg(feature = "debugger")]
+mod opcodes;
+#[cfg(feature = "std")]
+pub mod pacing;
+pub mod palette;
+pub mod png;
+pub mod ram_init;
+pub mod region;
+pub mod rng;
+#[cfg(feature = "debugger")]
+pub mod rom_builder;
+#[cfg(feature = "std")]
+pub mod storage;
+pub mod tiles;
+pub mod video;
@@ -0,0 +1,346 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// FM2 button order, most significant bit first.
+const BUTTON_CHARS: &[u8; 8] = b"RLDUTSBA";
+
+pub const COMMAND_SOFT_RESET: u8 = 0x01;
+pub const COMMAND_HARD_RESET: u8 = 0x02;
+
+/// Controller state for one frame. Pads use the joypad shift order:
+/// bit 0 = A, B, Select, Start, Up, Down, Left, bit 7 = Right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MovieFrame {
+    pub commands: u8,
+    pub pads: [u8; 4],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortDevice {
+    None,
+    Gamepad,
+}
+
+/// An FCEUX FM2 input movie (text form).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Movie {
+    pub emu_version: u32,
+    pub rerecord_count: u32,
+    pub pal: bool,
+    pub rom_filename: String,
+    /// `base64:` prefixed MD5 of the ROM, as written by FCEUX.
+    pub rom_checksum: String,
+    pub guid: String,
+    pub fourscore: bool,
+    pub ports: [PortDevice; 2],
+    pub comments: Vec<String>,
+    /// Header keys not interpreted here, kept so they survive a round trip.
+    pub extra: Vec<(String, String)>,
+    pub frames: Vec<MovieFrame>,
+}
+
+impl Default for Movie {
+    fn default() -> Self {
+        Movie {
+            emu_version: 22020,
+            rerecord_count: 0,
+            pal: false,
+            rom_filename: String::new(),
+            rom_checksum: String::new(),
+            guid: String::new(),
+            fourscore: false,
+            ports: [PortDevice::Gamepad, PortDevice::Gamepad],
+            comments: Vec::new(),
+            extra: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MovieError {
+    UnsupportedVersion(String),
+    /// `binary 1` movies store input as raw bytes and are not supported.
+    BinaryInput,
+    UnsupportedDevice(String),
+    InvalidHeader(usize),
+    InvalidInput(usize),
+}
+
+impl fmt::Display for MovieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MovieError::UnsupportedVersion(version) => {
+                write!(f, "unsupported FM2 version {}", version)
+            }
+            MovieError::BinaryInput => write!(f, "binary FM2 input is not supported"),
+            MovieError::UnsupportedDevice(device) => {
+                write!(f, "unsupported input device {}", device)
+            }
+            MovieError::InvalidHeader(line) => write!(f, "line {}: invalid header", line),
+            MovieError::InvalidInput(line) => write!(f, "line {}: invalid input record", line),
+        }
+    }
+}
+
+impl core::error::Error for MovieError {}
+
+impl Movie {
+    pub fn parse(text: &str) -> Result<Movie, MovieError> {
+        let mut movie = Movie::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('|') {
+                let frame = movie
+                    .parse_frame(line)
+                    .ok_or(MovieError::InvalidInput(line_number))?;
+                movie.frames.push(frame);
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let number = || {
+                value
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| MovieError::InvalidHeader(line_number))
+            };
+            match key {
+                "version" => {
+                    if value.trim() != "3" {
+                        return Err(MovieError::UnsupportedVersion(value.to_string()));
+                    }
+                }
+                "emuVersion" => movie.emu_version = number()?,
+                "rerecordCount" => movie.rerecord_count = number()?,
+                "palFlag" => movie.pal = number()? != 0,
+                "romFilename" => movie.rom_filename = value.to_string(),
+                "romChecksum" => movie.rom_checksum = value.to_string(),
+                "guid" => movie.guid = value.to_string(),
+                "fourscore" => movie.fourscore = number()? != 0,
+                "port0" | "port1" => {
+                    let device = match number()? {
+                        0 => PortDevice::None,
+                        1 => PortDevice::Gamepad,
+                        _ => return Err(MovieError::UnsupportedDevice(value.to_string())),
+                    };
+                    movie.ports[(key == "port1") as usize] = device;
+                }
+                "port2" => {}
+                "comment" => movie.comments.push(value.to_string()),
+                "binary" if number()? != 0 => return Err(MovieError::BinaryInput),
+                _ => movie.extra.push((key.to_string(), value.to_string())),
+            }
+        }
+        Ok(movie)
+    }
+
+    fn parse_frame(&self, line: &str) -> Option<MovieFrame> {
+        // "|commands|pad|pad|port2|", with four pads when the Four Score is used;
+        // an unplugged port leaves its field empty
+        let fields: Vec<&str> = line.split('|').collect();
+        let mut frame = MovieFrame {
+            commands: fields.get(1)?.trim().parse().ok()?,
+            ..MovieFrame::default()
+        };
+        for (slot, pad) in frame.pads.iter_mut().take(self.pad_count()).enumerate() {
+            let field = fields.get(2 + slot)?;
+            if !field.is_empty() {
+                *pad = parse_buttons(field)?;
+            }
+        }
+        Some(frame)
+    }
+
+    fn pad_count(&self) -> usize {
+        if self.fourscore {
+            4
+        } else {
+            2
+        }
+    }
+
+    pub fn to_fm2(&self) -> String {
+        self.to_string()
+    }
+
+    /// Records `input` for `frame`. Recording over an existing frame, as after
+    /// loading a save state, discards the rest of the movie and counts a re-record.
+    pub fn record(&mut self, frame: usize, input: MovieFrame) {
+        if frame < self.frames.len() {
+            self.frames.truncate(frame);
+            self.rerecord_count += 1;
+        }
+        self.frames.resize(frame, MovieFrame::default());
+        self.frames.push(input);
+    }
+
+    /// Input to apply on `frame` during playback; `None` once the movie has ended.
+    pub fn frame(&self, frame: usize) -> Option<&MovieFrame> {
+        self.frames.get(frame)
+    }
+}
+
+impl fmt::Display for Movie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let port = |device: PortDevice| match device {
+            PortDevice::None => 0,
+            PortDevice::Gamepad => 1,
+        };
+        writeln!(f, "version 3")?;
+        writeln!(f, "emuVersion {}", self.emu_version)?;
+        writeln!(f, "rerecordCount {}", self.rerecord_count)?;
+        writeln!(f, "palFlag {}", self.pal as u8)?;
+        writeln!(f, "romFilename {}", self.rom_filename)?;
+        writeln!(f, "romChecksum {}", self.rom_checksum)?;
+        writeln!(f, "guid {}", self.guid)?;
+        writeln!(f, "fourscore {}", self.fourscore as u8)?;
+        writeln!(f, "port0 {}", port(self.ports[0]))?;
+        writeln!(f, "port1 {}", port(self.ports[1]))?;
+        writeln!(f, "port2 0")?;
+        for (key, value) in &self.extra {
+            writeln!(f, "{} {}", key, value)?;
+        }
+        for comment in &self.comments {
+            writeln!(f, "comment {}", comment)?;
+        }
+
+        for frame in &self.frames {
+            write!(f, "|{}|", frame.commands)?;
+            for (slot, pad) in frame.pads.iter().take(self.pad_count()).enumerate() {
+                if self.fourscore || self.ports[slot] == PortDevice::Gamepad {
+                    write!(f, "{}", format_buttons(*pad))?;
+                }
+                write!(f, "|")?;
+            }
+            writeln!(f, "|")?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_buttons(field: &str) -> Option<u8> {
+    if field.len() != BUTTON_CHARS.len() {
+        return None;
+    }
+    Some(field.bytes().enumerate().fold(0, |buttons, (i, c)| {
+        if c == b'.' || c == b' ' {
+            buttons
+        } else {
+            buttons | (0x80 >> i)
+        }
+    }))
+}
+
+fn format_buttons(buttons: u8) -> String {
+    BUTTON_CHARS
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            if buttons & (0x80 >> i) != 0 {
+                *c as char
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FM2: &str = "version 3
+emuVersion 22020
+rerecordCount 7
+palFlag 0
+romFilename smb
+romChecksum base64:jjYwGG411HcjG/j9UOVM3Q==
+guid 0D4B84AE-5D23-4D5F-9C4B-B57C4D0A4E6F
+fourscore 0
+port0 1
+port1 0
+port2 0
+FDS 0
+comment author someone
+|0|R......A|||
+|1|....T...|||
+|0|........|||
+";
+
+    #[test]
+    fn parses_header_and_input() {
+        let movie = Movie::parse(FM2).unwrap();
+        assert_eq!(movie.rerecord_count, 7);
+        assert_eq!(movie.rom_filename, "smb");
+        assert_eq!(movie.ports, [PortDevice::Gamepad, PortDevice::None]);
+        assert_eq!(movie.comments, ["author someone"]);
+        assert_eq!(movie.extra, [("FDS".into(), "0".into())]);
+        assert_eq!(movie.frames.len(), 3);
+        assert_eq!(movie.frames[0].pads[0], 0x81);
+        assert_eq!(movie.frames[1].pads[0], 0x08);
+        assert_eq!(movie.frames[1].commands, COMMAND_SOFT_RESET);
+    }
+
+    #[test]
+    fn round_trips_text() {
+        assert_eq!(Movie::parse(FM2).unwrap().to_fm2(), FM2);
+    }
+
+    #[test]
+    fn round_trips_four_score() {
+        let mut movie = Movie {
+            fourscore: true,
+            ..Movie::default()
+        };
+        movie.record(
+            0,
+            MovieFrame {
+                commands: 0,
+                pads: [0x01, 0x02, 0x40, 0x80],
+            },
+        );
+        let text = movie.to_fm2();
+        assert!(text.ends_with("|0|.......A|......B.|.L......|R.......||\n"));
+        assert_eq!(Movie::parse(&text).unwrap(), movie);
+    }
+
+    #[test]
+    fn recording_over_a_frame_truncates_and_counts_a_rerecord() {
+        let mut movie = Movie::default();
+        let input = |pad| MovieFrame {
+            commands: 0,
+            pads: [pad, 0, 0, 0],
+        };
+        movie.record(2, input(1));
+        assert_eq!(movie.frames.len(), 3);
+        assert_eq!(movie.rerecord_count, 0);
+
+        movie.record(1, input(2));
+        assert_eq!(movie.frames, [input(0), input(2)]);
+        assert_eq!(movie.rerecord_count, 1);
+        assert_eq!(movie.frame(2), None);
+    }
+
+    #[test]
+    fn rejects_unsupported_movies() {
+        assert_eq!(
+            Movie::parse("version 2"),
+            Err(MovieError::UnsupportedVersion("2".into()))
+        );
+        assert_eq!(
+            Movie::parse("version 3\nbinary 1"),
+            Err(MovieError::BinaryInput)
+        );
+        assert_eq!(
+            Movie::parse("version 3\n|0|ABC||\n"),
+            Err(MovieError::InvalidInput(2))
+        );
+    }
+}
@@ -0,0 +1,230 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressingMode {
+    Implied,
+    Accumulator,
+    Immediate,
+    ZeroPage,
+    ZeroPageX,
+    ZeroPageY,
+    Absolute,
+    AbsoluteX,
+    AbsoluteY,
+    Indirect,
+    IndirectX,
+    IndirectY,
+    Relative,
+}
+
+impl AddressingMode {
+    /// Number of operand bytes following the opcode.
+    pub fn operand_len(self) -> usize {
+        match self {
+            AddressingMode::Implied | AddressingMode::Accumulator => 0,
+            AddressingMode::Immediate
+            | AddressingMode::ZeroPage
+            | AddressingMode::ZeroPageX
+            | AddressingMode::ZeroPageY
+            | AddressingMode::IndirectX
+            | AddressingMode::IndirectY
+            | AddressingMode::Relative => 1,
+            AddressingMode::Absolute
+            | AddressingMode::AbsoluteX
+            | AddressingMode::AbsoluteY
+            | AddressingMode::Indirect => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Opcode {
+    pub code: u8,
+    pub mnemonic: &'static str,
+    pub mode: AddressingMode,
+}
+
+impl Opcode {
+    const fn new(code: u8, mnemonic: &'static str, mode: AddressingMode) -> Self {
+        Opcode {
+            code,
+            mnemonic,
+            mode,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        1 + self.mode.operand_len()
+    }
+}
+
+use AddressingMode::*;
+
+/// The 151 official 6502 opcodes.
+pub static OPCODES: [Opcode; 151] = [
+    Opcode::new(0x69, "ADC", Immediate),
+    Opcode::new(0x65, "ADC", ZeroPage),
+    Opcode::new(0x75, "ADC", ZeroPageX),
+    Opcode::new(0x6D, "ADC", Absolute),
+    Opcode::new(0x7D, "ADC", AbsoluteX),
+    Opcode::new(0x79, "ADC", AbsoluteY),
+    Opcode::new(0x61, "ADC", IndirectX),
+    Opcode::new(0x71, "ADC", IndirectY),
+    Opcode::new(0x29, "AND", Immediate),
+    Opcode::new(0x25, "AND", ZeroPage),
+    Opcode::new(0x35, "AND", ZeroPageX),
+    Opcode::new(0x2D, "AND", Absolute),
+    Opcode::new(0x3D, "AND", AbsoluteX),
+    Opcode::new(0x39, "AND", AbsoluteY),
+    Opcode::new(0x21, "AND", IndirectX),
+    Opcode::new(0x31, "AND", IndirectY),
+    Opcode::new(0x0A, "ASL", Accumulator),
+    Opcode::new(0x06, "ASL", ZeroPage),
+    Opcode::new(0x16, "ASL", ZeroPageX),
+    Opcode::new(0x0E, "ASL", Absolute),
+    Opcode::new(0x1E, "ASL", AbsoluteX),
+    Opcode::new(0x90, "BCC", Relative),
+    Opcode::new(0xB0, "BCS", Relative),
+    Opcode::new(0xF0, "BEQ", Relative),
+    Opcode::new(0x30, "BMI", Relative),
+    Opcode::new(0xD0, "BNE", Relative),
+    Opcode::new(0x10, "BPL", Relative),
+    Opcode::new(0x50, "BVC", Relative),
+    Opcode::new(0x70, "BVS", Relative),
+    Opcode::new(0x24, "BIT", ZeroPage),
+    Opcode::new(0x2C, "BIT", Absolute),
+    Opcode::new(0x00, "BRK", Implied),
+    Opcode::new(0x18, "CLC", Implied),
+    Opcode::new(0xD8, "CLD", Implied),
+    Opcode::new(0x58, "CLI", Implied),
+    Opcode::new(0xB8, "CLV", Implied),
+    Opcode::new(0xC9, "CMP", Immediate),
+    Opcode::new(0xC5, "CMP", ZeroPage),
+    Opcode::new(0xD5, "CMP", ZeroPageX),
+    Opcode::new(0xCD, "CMP", Absolute),
+    Opcode::new(0xDD, "CMP", AbsoluteX),
+    Opcode::new(0xD9, "CMP", AbsoluteY),
+    Opcode::new(0xC1, "CMP", IndirectX),
+    Opcode::new(0xD1, "CMP", IndirectY),
+    Opcode::new(0xE0, "CPX", Immediate),
+    Opcode::new(0xE4, "CPX", ZeroPage),
+    Opcode::new(0xEC, "CPX", Absolute),
+    Opcode::new(0xC0, "CPY", Immediate),
+    Opcode::new(0xC4, "CPY", ZeroPage),
+    Opcode::new(0xCC, "CPY", Absolute),
+    Opcode::new(0xC6, "DEC", ZeroPage),
+    Opcode::new(0xD6, "DEC", ZeroPageX),
+    Opcode::new(0xCE, "DEC", Absolute),
+    Opcode::new(0xDE, "DEC", AbsoluteX),
+    Opcode::new(0xCA, "DEX", Implied),
+    Opcode::new(0x88, "DEY", Implied),
+    Opcode::new(0x49, "EOR", Immediate),
+    Opcode::new(0x45, "EOR", ZeroPage),
+    Opcode::new(0x55, "EOR", ZeroPageX),
+    Opcode::new(0x4D, "EOR", Absolute),
+    Opcode::new(0x5D, "EOR", AbsoluteX),
+    Opcode::new(0x59, "EOR", AbsoluteY),
+    Opcode::new(0x41, "EOR", IndirectX),
+    Opcode::new(0x51, "EOR", IndirectY),
+    Opcode::new(0xE6, "INC", ZeroPage),
+    Opcode::new(0xF6, "INC", ZeroPageX),
+    Opcode::new(0xEE, "INC", Absolute),
+    Opcode::new(0xFE, "INC", AbsoluteX),
+    Opcode::new(0xE8, "INX", Implied),
+    Opcode::new(0xC8, "INY", Implied),
+    Opcode::new(0x4C, "JMP", Absolute),
+    Opcode::new(0x6C, "JMP", Indirect),
+    Opcode::new(0x20, "JSR", Absolute),
+    Opcode::new(0xA9, "LDA", Immediate),
+    Opcode::new(0xA5, "LDA", ZeroPage),
+    Opcode::new(0xB5, "LDA", ZeroPageX),
+    Opcode::new(0xAD, "LDA", Absolute),
+    Opcode::new(0xBD, "LDA", AbsoluteX),
+    Opcode::new(0xB9, "LDA", AbsoluteY),
+    Opcode::new(0xA1, "LDA", IndirectX),
+    Opcode::new(0xB1, "LDA", IndirectY),
+    Opcode::new(0xA2, "LDX", Immediate),
+    Opcode::new(0xA6, "LDX", ZeroPage),
+    Opcode::new(0xB6, "LDX", ZeroPageY),
+    Opcode::new(0xAE, "LDX", Absolute),
+    Opcode::new(0xBE, "LDX", AbsoluteY),
+    Opcode::new(0xA0, "LDY", Immediate),
+    Opcode::new(0xA4, "LDY", ZeroPage),
+    Opcode::new(0xB4, "LDY", ZeroPageX),
+    Opcode::new(0xAC, "LDY", Absolute),
+    Opcode::new(0xBC, "LDY", AbsoluteX),
+    Opcode::new(0x4A, "LSR", Accumulator),
+    Opcode::new(0x46, "LSR", ZeroPage),
+    Opcode::new(0x56, "LSR", ZeroPageX),
+    Opcode::new(0x4E, "LSR", Absolute),
+    Opcode::new(0x5E, "LSR", AbsoluteX),
+    Opcode::new(0xEA, "NOP", Implied),
+    Opcode::new(0x09, "ORA", Immediate),
+    Opcode::new(0x05, "ORA", ZeroPage),
+    Opcode::new(0x15, "ORA", ZeroPageX),
+    Opcode::new(0x0D, "ORA", Absolute),
+    Opcode::new(0x1D, "ORA", AbsoluteX),
+    Opcode::new(0x19, "ORA", AbsoluteY),
+    Opcode::new(0x01, "ORA", IndirectX),
+    Opcode::new(0x11, "ORA", IndirectY),
+    Opcode::new(0x48, "PHA", Implied),
+    Opcode::new(0x08, "PHP", Implied),
+    Opcode::new(0x68, "PLA", Implied),
+    Opcode::new(0x28, "PLP", Implied),
+    Opcode::new(0x2A, "ROL", Accumulator),
+    Opcode::new(0x26, "ROL", ZeroPage),
+    Opcode::new(0x36, "ROL", ZeroPageX),
+    Opcode::new(0x2E, "ROL", Absolute),
+    Opcode::new(0x3E, "ROL", AbsoluteX),
+    Opcode::new(0x6A, "ROR", Accumulator),
+    Opcode::new(0x66, "ROR", ZeroPage),
+    Opcode::new(0x76, "ROR", ZeroPageX),
+    Opcode::new(0x6E, "ROR", Absolute),
+    Opcode::new(0x7E, "ROR", AbsoluteX),
+    Opcode::new(0x40, "RTI", Implied),
+    Opcode::new(0x60, "RTS", Implied),
+    Opcode::new(0xE9, "SBC", Immediate),
+    Opcode::new(0xE5, "SBC", ZeroPage),
+    Opcode::new(0xF5, "SBC", ZeroPageX),
+    Opcode::new(0xED, "SBC", Absolute),
+    Opcode::new(0xFD, "SBC", AbsoluteX),
+    Opcode::new(0xF9, "SBC", AbsoluteY),
+    Opcode::new(0xE1, "SBC", IndirectX),
+    Opcode::new(0xF1, "SBC", IndirectY),
+    Opcode::new(0x38, "SEC", Implied),
+    Opcode::new(0xF8, "SED", Implied),
+    Opcode::new(0x78, "SEI", Implied),
+    Opcode::new(0x85, "STA", ZeroPage),
+    Opcode::new(0x95, "STA", ZeroPageX),
+    Opcode::new(0x8D, "STA", Absolute),
+    Opcode::new(0x9D, "STA", AbsoluteX),
+    Opcode::new(0x99, "STA", AbsoluteY),
+    Opcode::new(0x81, "STA", IndirectX),
+    Opcode::new(0x91, "STA", IndirectY),
+    Opcode::new(0x86, "STX", ZeroPage),
+    Opcode::new(0x96, "STX", ZeroPageY),
+    Opcode::new(0x8E, "STX", Absolute),
+    Opcode::new(0x84, "STY", ZeroPage),
+    Opcode::new(0x94, "STY", ZeroPageX),
+    Opcode::new(0x8C, "STY", Absolute),
+    Opcode::new(0xAA, "TAX", Implied),
+    Opcode::new(0xA8, "TAY", Implied),
+    Opcode::new(0xBA, "TSX", Implied),
+    Opcode::new(0x8A, "TXA", Implied),
+    Opcode::new(0x9A, "TXS", Implied),
+    Opcode::new(0x98, "TYA", Implied),
+];
+
+pub fn lookup(mnemonic: &str, mode: AddressingMode) -> Option<&'static Opcode> {
+    OPCODES
+        .iter()
+        .find(|op| op.mode == mode && op.mnemonic.eq_ignore_ascii_case(mnemonic))
+}
+
+pub fn is_mnemonic(mnemonic: &str) -> bool {
+    OPCODES
+        .iter()
+        .any(|op| op.mnemonic.eq_ignore_ascii_case(mnemonic))
+}
+
+pub fn decode(code: u8) -> Option<&'static Opcode> {
+    OPCODES.iter().find(|op| op.code == code)
+}
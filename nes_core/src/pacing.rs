@@ -0,0 +1,157 @@
+use std::time::{Duration, Instant};
+
+use crate::region::Region;
+
+/// Sleeping is only trusted up to this close to the deadline; the rest is spun.
+const SPIN_MARGIN: Duration = Duration::from_millis(2);
+
+/// Holds emulation to the hardware frame rate.
+///
+/// Call `wait` once per emulated frame. If the host falls more than a frame
+/// behind, the schedule restarts from now instead of running fast to catch up.
+#[derive(Debug, Clone)]
+pub struct FrameLimiter {
+    period: Duration,
+    next_frame: Option<Instant>,
+}
+
+impl FrameLimiter {
+    pub fn new(region: Region) -> Self {
+        FrameLimiter::with_rate(region.frame_rate())
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `frames_per_second` is not a positive number.
+    pub fn with_rate(frames_per_second: f64) -> Self {
+        assert!(
+            frames_per_second > 0.0,
+            "frame rate must be positive, got {}",
+            frames_per_second
+        );
+        FrameLimiter {
+            period: Duration::from_secs_f64(1.0 / frames_per_second),
+            next_frame: None,
+        }
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Forgets the schedule, e.g. after the emulator was paused.
+    pub fn reset(&mut self) {
+        self.next_frame = None;
+    }
+
+    /// Blocks until the next frame is due.
+    pub fn wait(&mut self) {
+        let now = Instant::now();
+        let Some(deadline) = self.schedule(now) else {
+            return;
+        };
+        if let Some(remaining) = deadline.checked_duration_since(now) {
+            if remaining > SPIN_MARGIN {
+                std::thread::sleep(remaining - SPIN_MARGIN);
+            }
+            while Instant::now() < deadline {
+                std::hint::spin_loop();
+            }
+        }
+    }
+
+    /// Advances the schedule for a frame finished at `now`, returning when it
+    /// is due, or `None` when the schedule restarts and there is no wait.
+    fn schedule(&mut self, now: Instant) -> Option<Instant> {
+        match self.next_frame {
+            Some(deadline) if now <= deadline + self.period => {
+                self.next_frame = Some(deadline + self.period);
+                Some(deadline)
+            }
+            _ => {
+                self.next_frame = Some(now + self.period);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter() -> FrameLimiter {
+        FrameLimiter::with_rate(50.0)
+    }
+
+    const PERIOD: Duration = Duration::from_millis(20);
+
+    #[test]
+    fn period_follows_the_rate() {
+        assert_eq!(limiter().period(), PERIOD);
+    }
+
+    #[test]
+    fn frames_are_due_one_period_apart() {
+        let start = Instant::now();
+        let mut limiter = limiter();
+        assert_eq!(limiter.schedule(start), None);
+        assert_eq!(limiter.schedule(start + PERIOD / 2), Some(start + PERIOD));
+        assert_eq!(limiter.schedule(start + PERIOD), Some(start + PERIOD * 2));
+    }
+
+    #[test]
+    fn falling_less_than_a_frame_behind_catches_up() {
+        let start = Instant::now();
+        let mut limiter = limiter();
+        limiter.schedule(start);
+        // already past the deadline, so there is no wait, but the next frame
+        // is still due on the original schedule
+        assert_eq!(limiter.schedule(start + PERIOD * 2), Some(start + PERIOD));
+        assert_eq!(
+            limiter.schedule(start + PERIOD * 2),
+            Some(start + PERIOD * 2)
+        );
+    }
+
+    #[test]
+    fn falling_more_than_a_frame_behind_restarts() {
+        let start = Instant::now();
+        let mut limiter = limiter();
+        limiter.schedule(start);
+        let late = start + PERIOD * 2 + Duration::from_millis(1);
+        assert_eq!(limiter.schedule(late), None);
+        assert_eq!(limiter.schedule(late), Some(late + PERIOD));
+    }
+
+    #[test]
+    fn reset_restarts_the_schedule() {
+        let start = Instant::now();
+        let mut limiter = limiter();
+        limiter.schedule(start);
+        limiter.reset();
+        assert_eq!(limiter.schedule(start + PERIOD / 2), None);
+        assert_eq!(
+            limiter.schedule(start + PERIOD / 2),
+            Some(start + PERIOD / 2 + PERIOD)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "frame rate must be positive")]
+    fn rejects_a_zero_rate() {
+        FrameLimiter::with_rate(0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "frame rate must be positive")]
+    fn rejects_a_negative_rate() {
+        FrameLimiter::with_rate(-60.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "frame rate must be positive")]
+    fn rejects_nan() {
+        FrameLimiter::with_rate(f64::NAN);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::region::Region;
+
+pub type Rgb = (u8, u8, u8);
+
+#[rustfmt::skip]
+pub static SYSTEM_PALETTE: [Rgb; 64] = [
+    (0x80, 0x80, 0x80), (0x00, 0x3D, 0xA6), (0x00, 0x12, 0xB0), (0x44, 0x00, 0x96),
+    (0xA1, 0x00, 0x5E), (0xC7, 0x00, 0x28), (0xBA, 0x06, 0x00), (0x8C, 0x17, 0x00),
+    (0x5C, 0x2F, 0x00), (0x10, 0x45, 0x00), (0x05, 0x4A, 0x00), (0x00, 0x47, 0x2E),
+    (0x00, 0x41, 0x66), (0x00, 0x00, 0x00), (0x05, 0x05, 0x05), (0x05, 0x05, 0x05),
+    (0xC7, 0xC7, 0xC7), (0x00, 0x77, 0xFF), (0x21, 0x55, 0xFF), (0x82, 0x37, 0xFA),
+    (0xEB, 0x2F, 0xB5), (0xFF, 0x29, 0x50), (0xFF, 0x22, 0x00), (0xD6, 0x32, 0x00),
+    (0xC4, 0x62, 0x00), (0x35, 0x80, 0x00), (0x05, 0x8F, 0x00), (0x00, 0x8A, 0x55),
+    (0x00, 0x99, 0xCC), (0x21, 0x21, 0x21), (0x09, 0x09, 0x09), (0x09, 0x09, 0x09),
+    (0xFF, 0xFF, 0xFF), (0x0F, 0xD7, 0xFF), (0x69, 0xA2, 0xFF), (0xD4, 0x80, 0xFF),
+    (0xFF, 0x45, 0xF3), (0xFF, 0x61, 0x8B), (0xFF, 0x88, 0x33), (0xFF, 0x9C, 0x12),
+    (0xFA, 0xBC, 0x20), (0x9F, 0xE3, 0x0E), (0x2B, 0xF0, 0x35), (0x0C, 0xF0, 0xA4),
+    (0x05, 0xFB, 0xFF), (0x5E, 0x5E, 0x5E), (0x0D, 0x0D, 0x0D), (0x0D, 0x0D, 0x0D),
+    (0xFF, 0xFF, 0xFF), (0xA6, 0xFC, 0xFF), (0xB3, 0xEC, 0xFF), (0xDA, 0xAB, 0xEB),
+    (0xFF, 0xA8, 0xF9), (0xFF, 0xAB, 0xB3), (0xFF, 0xD2, 0xB0), (0xFF, 0xEF, 0xA6),
+    (0xFF, 0xF7, 0x9C), (0xD7, 0xE8, 0x95), (0xA6, 0xED, 0xAF), (0xA2, 0xF2, 0xDA),
+    (0x99, 0xFF, 0xFC), (0xDD, 0xDD, 0xDD), (0x11, 0x11, 0x11), (0x11, 0x11, 0x11),
+];
+
+const MASK_GRAYSCALE: u8 = 0b0000_0001;
+const MASK_EMPHASIZE_RED: u8 = 0b0010_0000;
+const MASK_EMPHASIZE_GREEN: u8 = 0b0100_0000;
+const MASK_EMPHASIZE_BLUE: u8 = 0b1000_0000;
+
+/// Converts a palette RAM entry to RGB under the given PPUMASK value.
+///
+/// Grayscale keeps only the luma column of the index. Each emphasis bit dims
+/// the two other channels to about 82%; PAL swaps the red and green bits.
+pub fn color(palette: &[Rgb; 64], index: u8, mask: u8, region: Region) -> Rgb {
+    let index = if mask & MASK_GRAYSCALE != 0 {
+        index & 0x30
+    } else {
+        index & 0x3F
+    };
+    let (mut r, mut g, mut b) = palette[index as usize];
+
+    let (red_bit, green_bit) = match region {
+        Region::Ntsc => (MASK_EMPHASIZE_RED, MASK_EMPHASIZE_GREEN),
+        Region::Pal => (MASK_EMPHASIZE_GREEN, MASK_EMPHASIZE_RED),
+    };
+    let dim = |channel: u8| (channel as u16 * 209 / 256) as u8;
+    if mask & red_bit != 0 {
+        g = dim(g);
+        b = dim(b);
+    }
+    if mask & green_bit != 0 {
+        r = dim(r);
+        b = dim(b);
+    }
+    if mask & MASK_EMPHASIZE_BLUE != 0 {
+        r = dim(r);
+        g = dim(g);
+    }
+    (r, g, b)
+}
+
+/// Knobs for `generate_palette`. The defaults decode the signal as-is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaletteParams {
+    /// Chroma rotation in degrees.
+    pub hue: f32,
+    /// Chroma multiplier.
+    pub saturation: f32,
+    /// Added to luma, in 0.0-1.0 units.
+    pub brightness: f32,
+    /// Luma multiplier.
+    pub contrast: f32,
+    /// Output is raised to `1 / gamma`; above 1.0 brightens mid-tones.
+    pub gamma: f32,
+}
+
+impl Default for PaletteParams {
+    fn default() -> Self {
+        PaletteParams {
+            hue: 0.0,
+            saturation: 1.0,
+            brightness: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+        }
+    }
+}
+
+/// Builds a palette by synthesizing the 2C02's composite signal for each
+/// colour and decoding it as YIQ, so colours can be tuned instead of loaded
+/// from a `.pal` file.
+#[cfg(feature = "std")]
+pub fn generate_palette(params: &PaletteParams) -> [Rgb; 64] {
+    use std::f32::consts::PI;
+
+    // signal voltages relative to sync: low levels for luma 0-3, then high levels
+    const LEVELS: [f32; 8] = [0.350, 0.518, 0.962, 1.550, 1.094, 1.506, 1.962, 1.962];
+    const BLACK: f32 = 0.518;
+    const WHITE: f32 = 1.962;
+    // aligns phase 0 of the colour clock with the decoder's I axis
+    const PHASE_OFFSET: f32 = 3.9;
+
+    let signal = |index: usize, phase: usize| {
+        let hue = index & 0x0F;
+        let level = if hue > 13 { 1 } else { (index >> 4) & 3 };
+        let low = LEVELS[level];
+        let high = LEVELS[4 + level];
+        let (low, high) = match hue {
+            0 => (high, high),
+            13.. => (low, low),
+            _ => (low, high),
+        };
+        if (hue + phase) % 12 < 6 {
+            high
+        } else {
+            low
+        }
+    };
+
+    let mut palette = [(0, 0, 0); 64];
+    for (index, rgb) in palette.iter_mut().enumerate() {
+        let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+        for phase in 0..12 {
+            let level = (signal(index, phase) - BLACK) / (WHITE - BLACK);
+            let angle = PI * (phase as f32 + PHASE_OFFSET) / 6.0 + params.hue.to_radians();
+            y += level;
+            i += level * angle.cos();
+            q += level * angle.sin();
+        }
+        let y = y / 12.0 * params.contrast + params.brightness;
+        let i = i / 6.0 * params.saturation;
+        let q = q / 6.0 * params.saturation;
+
+        let channel = |value: f32| {
+            let value = value.clamp(0.0, 1.0).powf(1.0 / params.gamma);
+            (value * 255.0).round() as u8
+        };
+        *rgb = (
+            channel(y + 0.946882 * i + 0.623557 * q),
+            channel(y - 0.274788 * i - 0.635691 * q),
+            channel(y - 1.108545 * i + 1.709007 * q),
+        );
+    }
+    palette
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: u8 = 0x30;
+
+    fn ntsc(index: u8, mask: u8) -> Rgb {
+        color(&SYSTEM_PALETTE, index, mask, Region::Ntsc)
+    }
+
+    #[test]
+    fn grayscale_keeps_the_luma_column() {
+        assert_eq!(ntsc(0x16, MASK_GRAYSCALE), SYSTEM_PALETTE[0x10]);
+        assert_eq!(ntsc(0x3F, MASK_GRAYSCALE), SYSTEM_PALETTE[0x30]);
+        assert_eq!(ntsc(0x0D, MASK_GRAYSCALE), SYSTEM_PALETTE[0x00]);
+        // only the low 6 bits select a colour
+        assert_eq!(ntsc(0xD6, 0), SYSTEM_PALETTE[0x16]);
+    }
+
+    #[test]
+    fn each_emphasis_bit_dims_the_other_channels() {
+        assert_eq!(ntsc(WHITE, 0), (0xFF, 0xFF, 0xFF));
+        assert_eq!(ntsc(WHITE, MASK_EMPHASIZE_RED), (0xFF, 208, 208));
+        assert_eq!(ntsc(WHITE, MASK_EMPHASIZE_GREEN), (208, 0xFF, 208));
+        assert_eq!(ntsc(WHITE, MASK_EMPHASIZE_BLUE), (208, 208, 0xFF));
+    }
+
+    #[test]
+    fn combined_emphasis_dims_twice() {
+        assert_eq!(
+            ntsc(WHITE, MASK_EMPHASIZE_RED | MASK_EMPHASIZE_GREEN),
+            (208, 208, 169)
+        );
+        assert_eq!(ntsc(WHITE, 0xE0), (169, 169, 169));
+        assert_eq!(ntsc(WHITE, 0xE0 | MASK_GRAYSCALE), (169, 169, 169));
+    }
+
+    #[test]
+    fn pal_swaps_red_and_green_emphasis() {
+        let pal = |mask| color(&SYSTEM_PALETTE, WHITE, mask, Region::Pal);
+        assert_eq!(pal(MASK_EMPHASIZE_RED), (208, 0xFF, 208));
+        assert_eq!(pal(MASK_EMPHASIZE_GREEN), (0xFF, 208, 208));
+        assert_eq!(pal(MASK_EMPHASIZE_BLUE), (208, 208, 0xFF));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn default_params_keep_black_and_white() {
+        let palette = generate_palette(&PaletteParams::default());
+        for index in [0x0D, 0x0E, 0x0F, 0x1D, 0x2E, 0x3F] {
+            assert_eq!(palette[index], (0, 0, 0), "${:02X}", index);
+        }
+        for index in [0x20, 0x30] {
+            assert_eq!(palette[index], (0xFF, 0xFF, 0xFF), "${:02X}", index);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hue_rotation_leaves_the_grays_alone() {
+        let default = generate_palette(&PaletteParams::default());
+        let rotated = generate_palette(&PaletteParams {
+            hue: 90.0,
+            ..PaletteParams::default()
+        });
+        for index in [0x00, 0x10, 0x20, 0x30] {
+            assert_eq!(rotated[index], default[index], "${:02X}", index);
+        }
+        assert_ne!(rotated[0x16], default[0x16]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn zero_saturation_gives_grays() {
+        let palette = generate_palette(&PaletteParams {
+            saturation: 0.0,
+            ..PaletteParams::default()
+        });
+        for (index, (r, g, b)) in palette.iter().enumerate() {
+            assert!(r == g && g == b, "${:02X} is {:?}", index, (r, g, b));
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::rng::Rng;
+
+/// Power-on contents used for WRAM, VRAM, OAM and palette RAM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RamInit {
+    #[default]
+    AllZero,
+    AllFF,
+    /// Four bytes of $00 followed by four bytes of $FF, repeated.
+    Alternating,
+    /// Pseudo-random bytes; the same seed always yields the same contents.
+    Random(u64),
+}
+
+impl RamInit {
+    pub fn fill(&self, memory: &mut [u8]) {
+        match *self {
+            RamInit::AllZero => memory.fill(0x00),
+            RamInit::AllFF => memory.fill(0xFF),
+            RamInit::Alternating => {
+                for (i, byte) in memory.iter_mut().enumerate() {
+                    *byte = if i & 0x04 == 0 { 0x00 } else { 0xFF };
+                }
+            }
+            RamInit::Random(seed) => Rng::new(seed).fill_bytes(memory),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(init: RamInit) -> [u8; 12] {
+        let mut memory = [0x55; 12];
+        init.fill(&mut memory);
+        memory
+    }
+
+    #[test]
+    fn fixed_patterns() {
+        assert_eq!(filled(RamInit::default()), [0x00; 12]);
+        assert_eq!(filled(RamInit::AllFF), [0xFF; 12]);
+        assert_eq!(
+            filled(RamInit::Alternating),
+            [0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn random_is_fixed_by_the_seed() {
+        assert_eq!(
+            filled(RamInit::Random(42))[..8],
+            [0x56, 0xC8, 0xCA, 0xF1, 0xC3, 0xD5, 0x34, 0x70]
+        );
+        assert_eq!(filled(RamInit::Random(7)), filled(RamInit::Random(7)));
+        assert_ne!(filled(RamInit::Random(7)), filled(RamInit::Random(8)));
+        assert_ne!(filled(RamInit::Random(0)), [0x00; 12]);
+    }
+}
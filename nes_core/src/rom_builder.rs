@@ -0,0 +1,237 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::asm::{self, AsmError};
+
+const PRG_BANK_SIZE: usize = 0x4000;
+const CHR_BANK_SIZE: usize = 0x2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mirroring {
+    Horizontal,
+    Vertical,
+}
+
+/// Builds mapper 0 iNES images for tests.
+///
+/// PRG is 32 KiB at $8000-$FFFF by default (16 KiB mirrors into $C000), filled
+/// with $FF. Reset defaults to $8000; an unset NMI or IRQ vector points at an
+/// `RTI` placed at $FFF9. The vectors are written last, over any code placed
+/// at $FFFA-$FFFF.
+#[derive(Debug, Clone)]
+pub struct TestRomBuilder {
+    prg_banks: usize,
+    /// `code` calls in order, applied to PRG by `build`.
+    code: Vec<(u16, Vec<u8>)>,
+    chr: Vec<u8>,
+    mirroring: Mirroring,
+    nmi: Option<u16>,
+    reset: u16,
+    irq: Option<u16>,
+}
+
+impl Default for TestRomBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestRomBuilder {
+    pub fn new() -> Self {
+        TestRomBuilder {
+            prg_banks: 2,
+            code: Vec::new(),
+            chr: vec![0; CHR_BANK_SIZE],
+            mirroring: Mirroring::Horizontal,
+            nmi: None,
+            reset: 0x8000,
+            irq: None,
+        }
+    }
+
+    /// Uses a single 16 KiB PRG bank, seen at both $8000 and $C000. Code placed
+    /// in either half lands in the same bank, whether added before or after.
+    pub fn prg_16k(mut self) -> Self {
+        self.prg_banks = 1;
+        self
+    }
+
+    /// Copies `bytes` into PRG at CPU address `address` ($8000-$FFFF).
+    pub fn code(mut self, address: u16, bytes: &[u8]) -> Self {
+        assert!(
+            address >= 0x8000,
+            "PRG starts at $8000, got ${:04X}",
+            address
+        );
+        self.code.push((address, bytes.to_vec()));
+        self
+    }
+
+    /// Assembles `source` at $8000 and places it there.
+    pub fn program(self, source: &str) -> Result<Self, AsmError> {
+        self.program_at(0x8000, source)
+    }
+
+    pub fn program_at(self, address: u16, source: &str) -> Result<Self, AsmError> {
+        let bytes = asm::assemble_at(address, source)?;
+        Ok(self.code(address, &bytes))
+    }
+
+    /// CHR ROM contents, padded to a multiple of 8 KiB. An empty slice means CHR RAM.
+    pub fn chr(mut self, data: &[u8]) -> Self {
+        let banks = data.len().div_ceil(CHR_BANK_SIZE);
+        self.chr = data.to_vec();
+        self.chr.resize(banks * CHR_BANK_SIZE, 0);
+        self
+    }
+
+    pub fn mirroring(mut self, mirroring: Mirroring) -> Self {
+        self.mirroring = mirroring;
+        self
+    }
+
+    pub fn nmi_vector(mut self, address: u16) -> Self {
+        self.nmi = Some(address);
+        self
+    }
+
+    pub fn reset_vector(mut self, address: u16) -> Self {
+        self.reset = address;
+        self
+    }
+
+    pub fn irq_vector(mut self, address: u16) -> Self {
+        self.irq = Some(address);
+        self
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        const RTI_ADDRESS: u16 = 0xFFF9;
+
+        let size = self.prg_banks * PRG_BANK_SIZE;
+        let mut prg = vec![0xFF; size];
+        let mut used = vec![false; size];
+        let offset = |address: u16, i: usize| (address as usize - 0x8000 + i) % size;
+        for (address, bytes) in &self.code {
+            for (i, byte) in bytes.iter().enumerate() {
+                let offset = offset(*address, i);
+                prg[offset] = *byte;
+                used[offset] = true;
+            }
+        }
+
+        if self.nmi.is_none() || self.irq.is_none() {
+            let rti = offset(RTI_ADDRESS, 0);
+            assert!(
+                !used[rti],
+                "code at ${:04X} would be replaced by the default RTI; set both the NMI and IRQ vectors",
+                RTI_ADDRESS
+            );
+            prg[rti] = 0x40;
+        }
+        let nmi = self.nmi.unwrap_or(RTI_ADDRESS);
+        let irq = self.irq.unwrap_or(RTI_ADDRESS);
+        let vectors = [nmi, self.reset, irq]
+            .into_iter()
+            .flat_map(u16::to_le_bytes);
+        for (i, byte) in vectors.enumerate() {
+            prg[offset(0xFFFA, i)] = byte;
+        }
+
+        let flags6 = match self.mirroring {
+            Mirroring::Horizontal => 0,
+            Mirroring::Vertical => 1,
+        };
+        let mut image = vec![
+            b'N',
+            b'E',
+            b'S',
+            0x1A,
+            self.prg_banks as u8,
+            (self.chr.len() / CHR_BANK_SIZE) as u8,
+            flags6,
+        ];
+        image.resize(16, 0);
+        image.extend_from_slice(&prg);
+        image.extend_from_slice(&self.chr);
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: usize = 16;
+
+    #[test]
+    fn header_describes_prg_chr_and_mirroring() {
+        let rom = TestRomBuilder::new()
+            .chr(&[0x11; 0x2001])
+            .mirroring(Mirroring::Vertical)
+            .build();
+        assert_eq!(&rom[..7], &[b'N', b'E', b'S', 0x1A, 2, 2, 1]);
+        assert_eq!(&rom[7..HEADER], &[0; 9]);
+        assert_eq!(rom.len(), HEADER + 0x8000 + 0x4000);
+
+        let rom = TestRomBuilder::new().prg_16k().chr(&[]).build();
+        assert_eq!(&rom[4..7], &[1, 0, 0]);
+        assert_eq!(rom.len(), HEADER + 0x4000);
+    }
+
+    #[test]
+    fn unset_interrupt_vectors_point_at_rti() {
+        let rom = TestRomBuilder::new().reset_vector(0x9000).build();
+        let prg = &rom[HEADER..HEADER + 0x8000];
+        assert_eq!(prg[0x7FF9], 0x40);
+        assert_eq!(&prg[0x7FFA..], &[0xF9, 0xFF, 0x00, 0x90, 0xF9, 0xFF]);
+
+        let rom = TestRomBuilder::new().nmi_vector(0x8100).build();
+        let prg = &rom[HEADER..HEADER + 0x8000];
+        assert_eq!(&prg[0x7FFA..], &[0x00, 0x81, 0x00, 0x80, 0xF9, 0xFF]);
+    }
+
+    #[test]
+    fn both_vectors_set_leaves_fff9_to_code() {
+        let rom = TestRomBuilder::new()
+            .nmi_vector(0x8000)
+            .irq_vector(0x8000)
+            .code(0xFFF9, &[0xEA])
+            .build();
+        assert_eq!(rom[HEADER + 0x7FF9], 0xEA);
+    }
+
+    #[test]
+    #[should_panic(expected = "default RTI")]
+    fn code_at_fff9_conflicts_with_default_rti() {
+        TestRomBuilder::new().code(0xFFF9, &[0xEA]).build();
+    }
+
+    #[test]
+    fn prg_16k_mirrors_8000_and_c000_in_any_order() {
+        let before = TestRomBuilder::new()
+            .code(0x8000, &[0xA9, 0x01])
+            .code(0xC010, &[0xEA])
+            .prg_16k()
+            .build();
+        let after = TestRomBuilder::new()
+            .prg_16k()
+            .code(0x8000, &[0xA9, 0x01])
+            .code(0xC010, &[0xEA])
+            .build();
+        assert_eq!(before, after);
+        let prg = &before[HEADER..HEADER + 0x4000];
+        assert_eq!(&prg[..2], &[0xA9, 0x01]);
+        assert_eq!(prg[0x10], 0xEA);
+        assert_eq!(&prg[0x3FFC..0x3FFE], &[0x00, 0x80]);
+    }
+
+    #[test]
+    fn program_assembles_at_its_address() {
+        let rom = TestRomBuilder::new()
+            .program_at(0xC000, "loop: JMP loop")
+            .unwrap()
+            .build();
+        assert_eq!(&rom[HEADER + 0x4000..HEADER + 0x4003], &[0x4C, 0x00, 0xC0]);
+    }
+}
@@ -0,0 +1,139 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::crc32::crc32;
+
+const APP_DIR: &str = "nes_by_rust";
+
+/// Per-game save, save-state and screenshot directories.
+///
+/// Games are keyed by the CRC-32 of the ROM contents (without the iNES header),
+/// so renaming the ROM file keeps its saves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Storage {
+    root: PathBuf,
+    game: String,
+}
+
+impl Storage {
+    /// Storage under the platform's per-user data directory, or `None` when
+    /// it cannot be determined.
+    pub fn for_rom(rom: &[u8]) -> Option<Storage> {
+        Some(Storage::with_root(data_dir()?, rom))
+    }
+
+    pub fn with_root(root: impl Into<PathBuf>, rom: &[u8]) -> Storage {
+        Storage {
+            root: root.into(),
+            game: format!("{:08X}", rom_hash(rom)),
+        }
+    }
+
+    pub fn save_dir(&self) -> PathBuf {
+        self.root.join("saves").join(&self.game)
+    }
+
+    pub fn state_dir(&self) -> PathBuf {
+        self.root.join("states").join(&self.game)
+    }
+
+    pub fn screenshot_dir(&self) -> PathBuf {
+        self.root.join("screenshots").join(&self.game)
+    }
+
+    /// Battery RAM file for the game.
+    pub fn save_file(&self) -> PathBuf {
+        self.save_dir().join("battery.sav")
+    }
+
+    pub fn state_file(&self, slot: u32) -> PathBuf {
+        self.state_dir().join(format!("slot{}.state", slot))
+    }
+
+    pub fn create_dirs(&self) -> io::Result<()> {
+        for dir in [self.save_dir(), self.state_dir(), self.screenshot_dir()] {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(())
+    }
+}
+
+/// CRC-32 of the PRG and CHR data, skipping the iNES header and trainer.
+pub fn rom_hash(rom: &[u8]) -> u32 {
+    let body = if rom.starts_with(b"NES\x1A") && rom.len() >= 16 {
+        let trainer = if rom[6] & 0x04 != 0 { 512 } else { 0 };
+        rom.get(16 + trainer..).unwrap_or(&[])
+    } else {
+        rom
+    };
+    crc32(body)
+}
+
+/// `$XDG_DATA_HOME` (or `~/.local/share`) on Unix, `~/Library/Application
+/// Support` on macOS and `%APPDATA%` on Windows, plus the emulator's directory.
+pub fn data_dir() -> Option<PathBuf> {
+    let non_empty = |name| env::var_os(name).filter(|value| !value.is_empty());
+    let base = if cfg!(windows) {
+        PathBuf::from(non_empty("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(non_empty("HOME")?).join("Library/Application Support")
+    } else {
+        match non_empty("XDG_DATA_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(non_empty("HOME")?).join(".local/share"),
+        }
+    };
+    Some(base.join(APP_DIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const BODY: &[u8] = b"123456789";
+    const BODY_HASH: u32 = 0xCBF4_3926;
+
+    fn headered(flags6: u8, trainer: &[u8]) -> Vec<u8> {
+        let mut rom = b"NES\x1A\x01\x00".to_vec();
+        rom.push(flags6);
+        rom.resize(16, 0);
+        rom.extend_from_slice(trainer);
+        rom.extend_from_slice(BODY);
+        rom
+    }
+
+    #[test]
+    fn rom_hash_skips_the_header_and_trainer() {
+        assert_eq!(rom_hash(BODY), BODY_HASH);
+        assert_eq!(rom_hash(&headered(0x00, &[])), BODY_HASH);
+        assert_eq!(rom_hash(&headered(0x04, &[0xAA; 512])), BODY_HASH);
+        // without the trainer bit the trainer bytes count as ROM data
+        assert_ne!(rom_hash(&headered(0x00, &[0xAA; 512])), BODY_HASH);
+        // a header cut short by a missing trainer hashes as empty
+        assert_eq!(rom_hash(&headered(0x04, &[])), 0);
+    }
+
+    #[test]
+    fn with_root_layout() {
+        let storage = Storage::with_root("root", &headered(0x00, &[]));
+        let root = Path::new("root");
+        assert_eq!(storage.save_dir(), root.join("saves").join("CBF43926"));
+        assert_eq!(storage.state_dir(), root.join("states").join("CBF43926"));
+        assert_eq!(
+            storage.screenshot_dir(),
+            root.join("screenshots").join("CBF43926")
+        );
+        assert_eq!(
+            storage.save_file(),
+            root.join("saves").join("CBF43926").join("battery.sav")
+        );
+        assert_eq!(
+            storage.state_file(3),
+            root.join("states").join("CBF43926").join("slot3.state")
+        );
+        assert_eq!(storage, Storage::with_root("root", BODY));
+    }
+}
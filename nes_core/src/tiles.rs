@@ -0,0 +1,188 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::palette::Rgb;
+
+/// Bytes per 8x8 tile: two bit planes of 8 rows.
+pub const TILE_BYTES: usize = 16;
+/// Bytes in one pattern table ($0000 or $1000).
+pub const PATTERN_TABLE_BYTES: usize = 0x1000;
+
+/// An 8x8 tile as colour indices 0-3, `[row][column]`.
+pub type Tile = [[u8; 8]; 8];
+
+/// Decodes a 2bpp tile; `bytes` must hold at least `TILE_BYTES`.
+pub fn decode_tile(bytes: &[u8]) -> Tile {
+    let mut tile = [[0; 8]; 8];
+    for (y, row) in tile.iter_mut().enumerate() {
+        let low = bytes[y];
+        let high = bytes[y + 8];
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let shift = 7 - x;
+            *pixel = ((low >> shift) & 1) | (((high >> shift) & 1) << 1);
+        }
+    }
+    tile
+}
+
+/// Tile number `index` of `chr`, counting from the start of the data.
+///
+/// # Panics
+///
+/// Panics if `chr` is too short to hold tile `index`.
+pub fn tile(chr: &[u8], index: usize) -> Tile {
+    let start = index * TILE_BYTES;
+    decode_tile(&chr[start..start + TILE_BYTES])
+}
+
+/// A grid of decoded tiles, one colour index (0-3) per pixel, row-major.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileSheet {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl TileSheet {
+    /// Lays out every whole tile of `chr`, `columns` tiles per row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `columns` is 0.
+    pub fn new(chr: &[u8], columns: usize) -> TileSheet {
+        assert!(columns > 0, "a tile sheet needs at least one column");
+        let count = chr.len() / TILE_BYTES;
+        let rows = count.div_ceil(columns);
+        let mut sheet = TileSheet {
+            width: columns * 8,
+            height: rows * 8,
+            pixels: vec![0; columns * 8 * rows * 8],
+        };
+        for index in 0..count {
+            sheet.draw_tile(
+                &tile(chr, index),
+                (index % columns) * 8,
+                (index / columns) * 8,
+            );
+        }
+        sheet
+    }
+
+    /// The 16x16-tile (128x128) view of pattern table 0 or 1 in `chr`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chr` does not hold the whole of pattern table `table`.
+    pub fn pattern_table(chr: &[u8], table: usize) -> TileSheet {
+        let start = table * PATTERN_TABLE_BYTES;
+        TileSheet::new(&chr[start..start + PATTERN_TABLE_BYTES], 16)
+    }
+
+    /// Copies `tile` with its top-left corner at pixel (x, y).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile does not fit inside the sheet at (x, y).
+    pub fn draw_tile(&mut self, tile: &Tile, x: usize, y: usize) {
+        assert!(
+            x + 8 <= self.width && y + 8 <= self.height,
+            "tile at ({}, {}) does not fit a {}x{} sheet",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+        for (dy, row) in tile.iter().enumerate() {
+            let start = (y + dy) * self.width + x;
+            self.pixels[start..start + 8].copy_from_slice(row);
+        }
+    }
+
+    /// RGB24 image of the sheet, colouring index `n` with `palette[n]`.
+    pub fn to_rgb(&self, palette: &[Rgb; 4]) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|index| {
+                let (r, g, b) = palette[*index as usize & 3];
+                [r, g, b]
+            })
+            .collect()
+    }
+
+    /// PNG image of the sheet, for debug dumps and bug reports.
+    pub fn to_png(&self, palette: &[Rgb; 4]) -> Vec<u8> {
+        crate::png::encode_rgb(self.width, self.height, &self.to_rgb(palette))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tile whose pixels all have colour `index`.
+    fn solid(index: u8) -> [u8; TILE_BYTES] {
+        let mut bytes = [0; TILE_BYTES];
+        bytes[..8].fill(if index & 1 != 0 { 0xFF } else { 0 });
+        bytes[8..].fill(if index & 2 != 0 { 0xFF } else { 0 });
+        bytes
+    }
+
+    #[test]
+    fn low_plane_is_bit_0_and_msb_is_leftmost() {
+        let mut bytes = [0; TILE_BYTES];
+        bytes[0] = 0b1100_0000;
+        bytes[8] = 0b1010_0000;
+        bytes[15] = 0b0000_0001;
+        let tile = decode_tile(&bytes);
+        assert_eq!(tile[0], [3, 1, 2, 0, 0, 0, 0, 0]);
+        assert_eq!(tile[7], [0, 0, 0, 0, 0, 0, 0, 2]);
+        assert!(tile[1..7].iter().all(|row| *row == [0; 8]));
+    }
+
+    #[test]
+    fn sheet_lays_tiles_out_row_by_row() {
+        let mut chr = Vec::new();
+        for index in 1..=3 {
+            chr.extend_from_slice(&solid(index));
+        }
+        // a trailing partial tile is left out
+        chr.extend_from_slice(&[0xFF; 4]);
+        let sheet = TileSheet::new(&chr, 2);
+        assert_eq!((sheet.width, sheet.height), (16, 16));
+        let pixel = |x: usize, y: usize| sheet.pixels[y * sheet.width + x];
+        assert_eq!(
+            [pixel(0, 0), pixel(15, 7), pixel(0, 8), pixel(15, 15)],
+            [1, 2, 3, 0]
+        );
+        assert_eq!(tile(&chr, 2), [[3; 8]; 8]);
+    }
+
+    #[test]
+    fn pattern_table_picks_the_right_half() {
+        let mut chr = [0; 2 * PATTERN_TABLE_BYTES];
+        let second = PATTERN_TABLE_BYTES + 17 * TILE_BYTES;
+        chr[second..second + TILE_BYTES].copy_from_slice(&solid(2));
+        assert!(TileSheet::pattern_table(&chr, 0)
+            .pixels
+            .iter()
+            .all(|pixel| *pixel == 0));
+        let sheet = TileSheet::pattern_table(&chr, 1);
+        assert_eq!((sheet.width, sheet.height), (128, 128));
+        // tile 17 is the second tile of the second row
+        assert_eq!(sheet.pixels[8 * 128 + 8], 2);
+        assert_eq!(sheet.pixels[8 * 128 + 16], 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn pattern_table_needs_the_whole_table() {
+        TileSheet::pattern_table(&[0; PATTERN_TABLE_BYTES], 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn draw_tile_rejects_tiles_past_the_edge() {
+        let mut sheet = TileSheet::new(&[0; 2 * TILE_BYTES], 2);
+        sheet.draw_tile(&[[1; 8]; 8], 12, 0);
+    }
+}
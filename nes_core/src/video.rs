@@ -0,0 +1,435 @@
+use alloc::vec::Vec;
+
+/// Software scalers over RGB24 frames (3 bytes per pixel, row-major).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scaler {
+    /// Integer nearest-neighbor scaling by the given factor.
+    Nearest(usize),
+    /// Scale2x (AdvMAME2x) edge-smoothing.
+    Scale2x,
+    /// Scale3x (AdvMAME3x) edge-smoothing.
+    Scale3x,
+    /// hq2x-style interpolating 2x scaler.
+    Hq2x,
+}
+
+impl Default for Scaler {
+    fn default() -> Self {
+        Scaler::Nearest(1)
+    }
+}
+
+impl Scaler {
+    pub fn factor(self) -> usize {
+        match self {
+            Scaler::Nearest(factor) => factor,
+            Scaler::Scale2x | Scaler::Hq2x => 2,
+            Scaler::Scale3x => 3,
+        }
+    }
+
+    pub fn output_size(self, width: usize, height: usize) -> (usize, usize) {
+        (width * self.factor(), height * self.factor())
+    }
+
+    pub fn scale(self, src: &[u8], width: usize, height: usize) -> Vec<u8> {
+        let mut dst = Vec::new();
+        self.scale_into(src, width, height, &mut dst);
+        dst
+    }
+
+    /// Scales into `dst`, reusing its allocation across frames.
+    pub fn scale_into(self, src: &[u8], width: usize, height: usize, dst: &mut Vec<u8>) {
+        assert_eq!(
+            src.len(),
+            width * height * 3,
+            "source is not a {}x{} RGB24 frame",
+            width,
+            height
+        );
+        let factor = self.factor();
+        assert!(factor >= 1, "scale factor must be at least 1");
+        dst.clear();
+        dst.resize(src.len() * factor * factor, 0);
+
+        let source = Source { src, width, height };
+        let out_width = width * factor;
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = (x as isize, y as isize);
+                match self {
+                    Scaler::Nearest(_) => {
+                        let e = source.pixel(sx, sy);
+                        for i in 0..factor * factor {
+                            put(
+                                dst,
+                                out_width,
+                                x * factor + i % factor,
+                                y * factor + i / factor,
+                                e,
+                            );
+                        }
+                    }
+                    Scaler::Scale2x => put_block(dst, out_width, x, y, &scale2x(&source, sx, sy)),
+                    Scaler::Scale3x => put_block(dst, out_width, x, y, &scale3x(&source, sx, sy)),
+                    Scaler::Hq2x => put_block(dst, out_width, x, y, &hq2x(&source, sx, sy)),
+                }
+            }
+        }
+    }
+}
+
+struct Source<'a> {
+    src: &'a [u8],
+    width: usize,
+    height: usize,
+}
+
+impl Source<'_> {
+    /// Pixel at (x, y) packed as 0xRRGGBB, clamped to the frame edges.
+    fn pixel(&self, x: isize, y: isize) -> u32 {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        let i = (y * self.width + x) * 3;
+        u32::from_be_bytes([0, self.src[i], self.src[i + 1], self.src[i + 2]])
+    }
+}
+
+fn put(dst: &mut [u8], width: usize, x: usize, y: usize, pixel: u32) {
+    let i = (y * width + x) * 3;
+    dst[i..i + 3].copy_from_slice(&pixel.to_be_bytes()[1..]);
+}
+
+/// Writes the N x N block produced for source pixel (x, y).
+fn put_block<const N: usize>(
+    dst: &mut [u8],
+    width: usize,
+    x: usize,
+    y: usize,
+    block: &[[u32; N]; N],
+) {
+    for (dy, row) in block.iter().enumerate() {
+        for (dx, pixel) in row.iter().enumerate() {
+            put(dst, width, x * N + dx, y * N + dy, *pixel);
+        }
+    }
+}
+
+//  A B C
+//  D E F
+//  G H I
+fn scale2x(source: &Source, x: isize, y: isize) -> [[u32; 2]; 2] {
+    let b = source.pixel(x, y - 1);
+    let d = source.pixel(x - 1, y);
+    let e = source.pixel(x, y);
+    let f = source.pixel(x + 1, y);
+    let h = source.pixel(x, y + 1);
+
+    if b == h || d == f {
+        return [[e; 2]; 2];
+    }
+    [
+        [if d == b { d } else { e }, if b == f { f } else { e }],
+        [if d == h { d } else { e }, if h == f { f } else { e }],
+    ]
+}
+
+fn scale3x(source: &Source, x: isize, y: isize) -> [[u32; 3]; 3] {
+    let a = source.pixel(x - 1, y - 1);
+    let b = source.pixel(x, y - 1);
+    let c = source.pixel(x + 1, y - 1);
+    let d = source.pixel(x - 1, y);
+    let e = source.pixel(x, y);
+    let f = source.pixel(x + 1, y);
+    let g = source.pixel(x - 1, y + 1);
+    let h = source.pixel(x, y + 1);
+    let i = source.pixel(x + 1, y + 1);
+
+    if b == h || d == f {
+        return [[e; 3]; 3];
+    }
+    [
+        [
+            if d == b { d } else { e },
+            if (d == b && e != c) || (b == f && e != a) {
+                b
+            } else {
+                e
+            },
+            if b == f { f } else { e },
+        ],
+        [
+            if (d == b && e != g) || (d == h && e != a) {
+                d
+            } else {
+                e
+            },
+            e,
+            if (b == f && e != i) || (h == f && e != c) {
+                f
+            } else {
+                e
+            },
+        ],
+        [
+            if d == h { d } else { e },
+            if (d == h && e != i) || (h == f && e != g) {
+                h
+            } else {
+                e
+            },
+            if h == f { f } else { e },
+        ],
+    ]
+}
+
+/// hq2x: neighbours are compared with the centre in YUV, and each output
+/// pixel blends the centre with the three neighbours touching its corner,
+/// using hq2x's interpolation weights for the pattern of differences.
+fn hq2x(source: &Source, x: isize, y: isize) -> [[u32; 2]; 2] {
+    let pixel = |dx, dy| source.pixel(x + dx, y + dy);
+    let e = pixel(0, 0);
+    [
+        [
+            hq2x_corner(e, pixel(-1, -1), pixel(0, -1), pixel(-1, 0)),
+            hq2x_corner(e, pixel(1, -1), pixel(0, -1), pixel(1, 0)),
+        ],
+        [
+            hq2x_corner(e, pixel(-1, 1), pixel(0, 1), pixel(-1, 0)),
+            hq2x_corner(e, pixel(1, 1), pixel(0, 1), pixel(1, 0)),
+        ],
+    ]
+}
+
+/// One output pixel from the centre `e`, the diagonal neighbour and the two
+/// edge neighbours sharing that corner.
+fn hq2x_corner(e: u32, diagonal: u32, vertical: u32, horizontal: u32) -> u32 {
+    let diagonal_differs = yuv_differs(e, diagonal);
+    match (yuv_differs(e, vertical), yuv_differs(e, horizontal)) {
+        // an edge runs across the corner if both sides match each other
+        (true, true) if !yuv_differs(vertical, horizontal) => {
+            if diagonal_differs {
+                blend(&[(e, 2), (vertical, 3), (horizontal, 3)])
+            } else {
+                blend(&[(e, 2), (vertical, 1), (horizontal, 1)])
+            }
+        }
+        (true, true) if diagonal_differs => e,
+        (true, true) => blend(&[(e, 3), (diagonal, 1)]),
+        (true, false) if diagonal_differs => blend(&[(e, 3), (horizontal, 1)]),
+        (true, false) => blend(&[(e, 2), (diagonal, 1), (horizontal, 1)]),
+        (false, true) if diagonal_differs => blend(&[(e, 3), (vertical, 1)]),
+        (false, true) => blend(&[(e, 2), (diagonal, 1), (vertical, 1)]),
+        (false, false) => blend(&[(e, 2), (vertical, 1), (horizontal, 1)]),
+    }
+}
+
+/// hq2x's similarity test: Y, U and V each within a fixed threshold.
+fn yuv_differs(a: u32, b: u32) -> bool {
+    let yuv = |pixel: u32| {
+        let [_, r, g, b] = pixel.to_be_bytes().map(i32::from);
+        ((r + g + b) >> 2, (r - b) >> 2, (2 * g - r - b) >> 3)
+    };
+    let (ya, ua, va) = yuv(a);
+    let (yb, ub, vb) = yuv(b);
+    (ya - yb).abs() > 0x30 || (ua - ub).abs() > 0x07 || (va - vb).abs() > 0x06
+}
+
+/// Weighted average of packed pixels; the weights must sum to a power of two.
+fn blend(parts: &[(u32, u32)]) -> u32 {
+    let total: u32 = parts.iter().map(|(_, weight)| weight).sum();
+    let channel = |shift: u32| {
+        let sum: u32 = parts
+            .iter()
+            .map(|(pixel, weight)| (pixel >> shift & 0xFF) * weight)
+            .sum();
+        (sum / total) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
+
+/// Shape of one output pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PixelAspect {
+    #[default]
+    Square,
+    /// 8:7, the pixel shape of an NTSC NES on a 4:3 television.
+    Ntsc,
+}
+
+impl PixelAspect {
+    fn scaled_width(self, width: u32) -> u32 {
+        match self {
+            PixelAspect::Square => width,
+            PixelAspect::Ntsc => (width * 8 + 3) / 7,
+        }
+    }
+}
+
+/// Where to draw a frame inside a window, centred with black borders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    /// Largest placement of a `frame` sized image (width, height) that fits in
+    /// `window`. With `integer` set the frame is only scaled by whole
+    /// multiples, never below 1x, to keep pixels uniform.
+    pub fn fit(
+        frame: (u32, u32),
+        window: (u32, u32),
+        aspect: PixelAspect,
+        integer: bool,
+    ) -> Viewport {
+        let (frame_width, frame_height) = (aspect.scaled_width(frame.0), frame.1);
+        let (width, height) = if integer {
+            let scale = (window.0 / frame_width).min(window.1 / frame_height).max(1);
+            (frame_width * scale, frame_height * scale)
+        } else {
+            let scale =
+                (window.0 as f32 / frame_width as f32).min(window.1 as f32 / frame_height as f32);
+            (
+                (frame_width as f32 * scale) as u32,
+                (frame_height as f32 * scale) as u32,
+            )
+        };
+        Viewport {
+            x: window.0.saturating_sub(width) / 2,
+            y: window.1.saturating_sub(height) / 2,
+            width,
+            height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const A: [u8; 3] = [0xFF, 0, 0];
+    const B: [u8; 3] = [0, 0, 0xFF];
+
+    fn image(pixels: &[[u8; 3]]) -> Vec<u8> {
+        pixels.concat()
+    }
+
+    /// A 2x2 checkerboard, the smallest image with diagonal edges.
+    fn checker() -> Vec<u8> {
+        image(&[A, B, B, A])
+    }
+
+    #[test]
+    fn nearest_repeats_pixels() {
+        let out = Scaler::Nearest(2).scale(&image(&[A, B]), 2, 1);
+        assert_eq!(out, image(&[A, A, B, B, A, A, B, B]));
+        assert_eq!(Scaler::default().scale(&checker(), 2, 2), checker());
+    }
+
+    #[test]
+    fn scale2x_smooths_diagonals() {
+        let out = Scaler::Scale2x.scale(&checker(), 2, 2);
+        #[rustfmt::skip]
+        let expected = image(&[
+            A, A, B, B,
+            A, B, A, B,
+            B, A, B, A,
+            B, B, A, A,
+        ]);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn scale3x_smooths_diagonals() {
+        let out = Scaler::Scale3x.scale(&checker(), 2, 2);
+        assert_eq!(Scaler::Scale3x.output_size(2, 2), (6, 6));
+        let block = |bx: usize, by: usize| {
+            let mut pixels = vec![];
+            for y in by * 3..by * 3 + 3 {
+                let row = (y * 6 + bx * 3) * 3;
+                pixels.extend_from_slice(&out[row..row + 9]);
+            }
+            pixels
+        };
+        assert_eq!(block(0, 0), image(&[A, A, A, A, A, B, A, B, B]));
+        assert_eq!(block(1, 1), image(&[B, B, A, B, A, A, A, A, A]));
+    }
+
+    #[test]
+    fn hq2x_blends_across_diagonals() {
+        let out = Scaler::Hq2x.scale(&checker(), 2, 2);
+        // halfway between A and B
+        const M: [u8; 3] = [0x7F, 0, 0x7F];
+        #[rustfmt::skip]
+        let expected = image(&[
+            A, A, B, B,
+            A, M, M, B,
+            B, M, M, A,
+            B, B, A, A,
+        ]);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn hq2x_treats_close_colours_as_equal() {
+        let near = [0xF8, 0, 0];
+        let out = Scaler::Hq2x.scale(&image(&[A, near]), 2, 1);
+        assert_eq!(out.len(), 4 * 2 * 3);
+        // no edge between A and `near`, so the centre is blended with its sides
+        assert_eq!(&out[3..6], &[0xFD, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 1")]
+    fn nearest_rejects_zero_factor() {
+        Scaler::Nearest(0).scale(&checker(), 2, 2);
+    }
+
+    #[test]
+    fn flat_images_stay_flat() {
+        let flat = image(&[A; 9]);
+        for scaler in [Scaler::Scale2x, Scaler::Scale3x, Scaler::Hq2x] {
+            let factor = scaler.factor();
+            assert_eq!(
+                scaler.scale(&flat, 3, 3),
+                image(&vec![A; 9 * factor * factor])
+            );
+        }
+    }
+
+    fn viewport(x: u32, y: u32, width: u32, height: u32) -> Viewport {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn integer_fit_centres_whole_multiples() {
+        let fit = Viewport::fit((256, 240), (800, 600), PixelAspect::Square, true);
+        assert_eq!(fit, viewport(144, 60, 512, 480));
+        // 8:7 makes the frame 293 pixels wide before scaling
+        let fit = Viewport::fit((256, 240), (800, 600), PixelAspect::Ntsc, true);
+        assert_eq!(fit, viewport(107, 60, 586, 480));
+    }
+
+    #[test]
+    fn integer_fit_never_scales_below_1x() {
+        let fit = Viewport::fit((256, 240), (200, 100), PixelAspect::Square, true);
+        assert_eq!(fit, viewport(0, 0, 256, 240));
+    }
+
+    #[test]
+    fn free_fit_fills_the_limiting_dimension() {
+        let fit = Viewport::fit((256, 240), (800, 600), PixelAspect::Square, false);
+        assert_eq!(fit, viewport(80, 0, 640, 600));
+        let fit = Viewport::fit((256, 240), (1024, 960), PixelAspect::Square, false);
+        assert_eq!(fit, viewport(0, 0, 1024, 960));
+    }
+}